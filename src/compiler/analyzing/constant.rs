@@ -0,0 +1,159 @@
+use crate::generating::llvm::{Constant, RegisterFormat};
+use crate::parsing::ast::{ASTNode, NodeKind};
+use crate::scanning::token::*;
+
+// Evaluate an expression made only of literals, named constants and operators at compile time
+// lookup gives the value of a named constant, or None if the name isn't a constant
+// Returns None if the expression isn't constant or can't be computed, e.g. because it overflows
+pub fn evaluate(node: &ASTNode, lookup: &dyn Fn(&str) -> Option<Constant>) -> Option<Constant> {
+	match &node.kind {
+		NodeKind::Literal(Literal::Integer(x)) => Some(Constant::Integer { value: *x as i128, format: RegisterFormat::Integer }),
+		NodeKind::Literal(Literal::Float(x)) => Some(Constant::Float { value: *x, format: RegisterFormat::Float64 }),
+		NodeKind::Literal(Literal::Identifier(Identifier::Symbol(name))) => lookup(name),
+		NodeKind::Unary { token, expr } => match (token, evaluate(expr, lookup)?) {
+			(Token::Minus, Constant::Integer { value, format }) if format.is_signed() => integer(value.checked_neg()?, format),
+			(Token::Minus, Constant::Float { value, format }) => Some(float(-value, format)),
+			(Token::Exclamation, Constant::Boolean(x)) => Some(Constant::Boolean(!x)),
+			_ => None,
+		},
+		NodeKind::Binary { token, left, right } => {
+			match (evaluate(left, lookup)?, evaluate(right, lookup)?) {
+				(Constant::Integer { value: left_value, format: left_format }, Constant::Integer { value: right_value, format: right_format }) => {
+					let format = operand_format(left, right, left_format, right_format)?;
+					if !format.can_hold(left_value) || !format.can_hold(right_value) {
+						return None;
+					}
+
+					evaluate_integer(token, left_value, right_value, format)
+				},
+				(Constant::Float { value: left_value, format: left_format }, Constant::Float { value: right_value, format: right_format }) => {
+					let format = operand_format(left, right, left_format, right_format)?;
+
+					evaluate_float(token, round(left_value, &format), round(right_value, &format), format)
+				},
+				(Constant::Boolean(left), Constant::Boolean(right)) => evaluate_boolean(token, left, right),
+				_ => None,
+			}
+		},
+		_ => None,
+	}
+}
+
+// Evaluate an expression of number literals as a value of the given format
+// Integer literals can only become integers, and float literals floats
+pub fn evaluate_literal(node: &ASTNode, format: &RegisterFormat) -> Option<Constant> {
+	match evaluate(node, &|_| None)? {
+		Constant::Integer { value, .. } if format.is_integer() => integer(value, format.clone()),
+		Constant::Float { value, .. } if format.is_float() => Some(float(value, format.clone())),
+		_ => None,
+	}
+}
+
+// Format an expression of number literals has where no format is expected, taken from its first literal
+pub fn own_format(node: &ASTNode) -> RegisterFormat {
+	match &node.kind {
+		NodeKind::Literal(Literal::Float(_)) => RegisterFormat::Float64,
+		NodeKind::Unary { expr, .. } => own_format(expr),
+		NodeKind::Binary { left, .. } => own_format(left),
+		_ => RegisterFormat::Integer,
+	}
+}
+
+// Exact value of an expression of integer literals, whatever format it's used as, or None if it can't be computed
+pub fn integer_value(node: &ASTNode) -> Option<i128> {
+	match &node.kind {
+		NodeKind::Literal(Literal::Integer(x)) => Some(*x as i128),
+		NodeKind::Unary { token: Token::Minus, expr } => integer_value(expr)?.checked_neg(),
+		NodeKind::Binary { token, left, right } => {
+			let (left, right) = (integer_value(left)?, integer_value(right)?);
+			match token {
+				Token::Asterisk => left.checked_mul(right),
+				Token::Minus => left.checked_sub(right),
+				Token::Plus => left.checked_add(right),
+				Token::Slash => left.checked_div(right),
+				Token::Percent => left.checked_rem(right),
+				_ => None,
+			}
+		},
+		_ => None,
+	}
+}
+
+// Whether node is made only of number literals, so its format can be taken from where it's used
+pub fn is_number_literal(node: &ASTNode) -> bool {
+	match &node.kind {
+		NodeKind::Literal(Literal::Integer(_) | Literal::Float(_)) => true,
+		NodeKind::Unary { token: Token::Minus, expr } => is_number_literal(expr),
+		NodeKind::Binary { token: Token::Asterisk | Token::Minus | Token::Plus | Token::Slash | Token::Percent, left, right } => is_number_literal(left) && is_number_literal(right),
+		_ => false,
+	}
+}
+
+// Format both operands of a binary operator are evaluated in; a literal takes the format of the other operand
+fn operand_format(left: &ASTNode, right: &ASTNode, left_format: RegisterFormat, right_format: RegisterFormat) -> Option<RegisterFormat> {
+	if is_number_literal(left) {
+		Some(right_format)
+	} else if is_number_literal(right) || left_format == right_format {
+		Some(left_format)
+	} else {
+		None
+	}
+}
+
+// Integer constant of the given format, or None if value is out of its range
+fn integer(value: i128, format: RegisterFormat) -> Option<Constant> {
+	format.can_hold(value).then_some(Constant::Integer { value, format })
+}
+
+// Float constant of the given format, rounded to its precision
+fn float(value: f64, format: RegisterFormat) -> Constant {
+	Constant::Float { value: round(value, &format), format }
+}
+
+fn round(value: f64, format: &RegisterFormat) -> f64 {
+	if *format == RegisterFormat::Float32 { value as f32 as f64 } else { value }
+}
+
+fn evaluate_integer(token: &Token, left: i128, right: i128, format: RegisterFormat) -> Option<Constant> {
+	match token {
+		Token::Asterisk => integer(left.checked_mul(right)?, format),
+		Token::Minus => integer(left.checked_sub(right)?, format),
+		Token::Plus => integer(left.checked_add(right)?, format),
+		Token::Slash => integer(left.checked_div(right)?, format),
+		Token::Percent => integer(left.checked_rem(right)?, format),
+		Token::Equals2 => Some(Constant::Boolean(left == right)),
+		Token::ExclamationEqual => Some(Constant::Boolean(left != right)),
+		Token::LessThan => Some(Constant::Boolean(left < right)),
+		Token::LessThanEqual => Some(Constant::Boolean(left <= right)),
+		Token::GreaterThan => Some(Constant::Boolean(left > right)),
+		Token::GreaterThanEqual => Some(Constant::Boolean(left >= right)),
+		_ => None,
+	}
+}
+
+fn evaluate_float(token: &Token, left: f64, right: f64, format: RegisterFormat) -> Option<Constant> {
+	match token {
+		Token::Asterisk => Some(float(left * right, format)),
+		Token::Minus => Some(float(left - right, format)),
+		Token::Plus => Some(float(left + right, format)),
+		Token::Slash => Some(float(left / right, format)),
+		Token::Percent => Some(float(left % right, format)),
+		Token::Equals2 => Some(Constant::Boolean(left == right)),
+		Token::ExclamationEqual => Some(Constant::Boolean(left != right)),
+		Token::LessThan => Some(Constant::Boolean(left < right)),
+		Token::LessThanEqual => Some(Constant::Boolean(left <= right)),
+		Token::GreaterThan => Some(Constant::Boolean(left > right)),
+		Token::GreaterThanEqual => Some(Constant::Boolean(left >= right)),
+		_ => None,
+	}
+}
+
+fn evaluate_boolean(token: &Token, left: bool, right: bool) -> Option<Constant> {
+	match token {
+		Token::Ampersand2 => Some(Constant::Boolean(left && right)),
+		Token::Pipe2 => Some(Constant::Boolean(left || right)),
+		Token::Equals2 => Some(Constant::Boolean(left == right)),
+		Token::ExclamationEqual => Some(Constant::Boolean(left != right)),
+		_ => None,
+	}
+}
\ No newline at end of file
@@ -0,0 +1,1156 @@
+pub mod constant;
+
+use std::collections::BTreeMap;
+
+use crate::error::*;
+use crate::error::diagnostic::Diagnostics;
+
+use crate::generating::llvm::*;
+use crate::generating::writer::RUNTIME_SYMBOLS;
+use crate::parsing::ast::{ASTNode, EnumVariant, FunctionParameter, MatchArm, NodeKind, Pattern, Program, StructField, Type};
+use crate::scanning::token::*;
+
+// Checks a whole program before any code is generated: every name must resolve,
+// every operation must be given operands of the right format, and every function
+// with a return type must return on all paths
+#[derive(Debug)]
+pub struct Analyzer {
+	local_symbol_table: ScopedSymbolTable,
+	global_symbol_table: SymbolTable,
+	types: TypeRegistry,
+	return_fmt: RegisterFormat,
+	// Values moved before each break and each continue of the loops being analyzed, innermost last
+	loop_moves: Vec<(BTreeMap<String, String>, BTreeMap<String, String>)>,
+	// Registers of the locals whose box or vec has been moved out, along with their names; a local can't be used
+	// while it's in here, and leaves once it's assigned a new value
+	moved: BTreeMap<String, String>,
+	// Registers of the locals that may hold a reference to another local, along with that local's name
+	borrows: BTreeMap<String, String>,
+	diagnostics: Diagnostics,
+}
+
+impl Default for Analyzer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Analyzer {
+	pub fn new() -> Self {
+		Self {
+			local_symbol_table: ScopedSymbolTable::new(64),
+			global_symbol_table: SymbolTable::new(64),
+			types: TypeRegistry::new(),
+			return_fmt: RegisterFormat::Void,
+			loop_moves: Vec::new(),
+			moved: BTreeMap::new(),
+			borrows: BTreeMap::new(),
+			diagnostics: Diagnostics::new(),
+		}
+	}
+
+	pub fn diagnostics(&self) -> &Diagnostics {
+		&self.diagnostics
+	}
+
+	pub fn take_diagnostics(&mut self) -> Diagnostics {
+		std::mem::take(&mut self.diagnostics)
+	}
+
+	// Analyze every global statement; errors are recorded in diagnostics
+	pub fn analyze(&mut self, program: &Program) {
+		// Declare structs and enums before anything else, since any signature or declaration may use them
+		for statement in &program.statements {
+			let result = match &statement.kind {
+				NodeKind::StructDefinition { name, fields } => self.declare_struct(name, fields),
+				NodeKind::EnumDefinition { name, variants } => self.declare_enum(name, variants),
+				_ => Ok(()),
+			};
+
+			if let Err(error) = result {
+				self.diagnostics.push(error.with_span(&statement.span));
+			}
+		}
+
+		// Declare every function and global first so function bodies can use them regardless of definition order
+		for statement in &program.statements {
+			let result = match &statement.kind {
+				NodeKind::FunctionDefinition { name, parameters, return_type, .. } => self.declare_function(name, parameters, return_type),
+				NodeKind::Let { name, val_type, value } => self.analyze_global(name, val_type.as_ref(), value.as_deref(), false).map(|_| ()),
+				NodeKind::Const { .. } => self.analyze_node(statement).map(|_| ()),
+				NodeKind::LetTuple { value, .. } => self.analyze_value(value).and_then(|received| Err(Error::GlobalTypeUnsupported { received })),
+				_ => Ok(()),
+			};
+
+			if let Err(error) = result {
+				self.diagnostics.push(error.with_span(&statement.span));
+			}
+		}
+
+		for statement in &program.statements {
+			if let NodeKind::FunctionDefinition { .. } = &statement.kind {
+				if let Err(error) = self.analyze_node(statement) {
+					self.diagnostics.push(error);
+				}
+			}
+		}
+	}
+
+	// Analyze each statement of a block in a scope of its own, recording errors so the rest of the block is still checked
+	pub fn analyze_block(&mut self, block: &[ASTNode]) {
+		self.local_symbol_table.push_scope();
+		for statement in block {
+			if let Err(error) = self.analyze_node(statement) {
+				self.diagnostics.push(error);
+			}
+		}
+		self.local_symbol_table.pop_scope();
+	}
+
+	// Get the format a node evaluates to, checking it along the way
+	pub fn analyze_node(&mut self, node: &ASTNode) -> Result<RegisterFormat> {
+		match &node.kind {
+			NodeKind::Literal(x) => self.analyze_literal(x),
+			NodeKind::Binary { token, left, right } => self.analyze_binary(token, left, right),
+			NodeKind::Unary { token, expr } => self.analyze_unary(token, expr),
+			NodeKind::Cast { expr, cast_type } => self.analyze_cast(expr, cast_type),
+			NodeKind::Reference { expr, mutable } => self.analyze_reference(expr, *mutable),
+			NodeKind::Deref { expr } => self.analyze_deref(expr),
+			NodeKind::BoxNew { value } => self.analyze_box_new(value, None),
+			// An empty vec can only take its element format from where it's used
+			NodeKind::VecNew => Err(Error::ElementTypeUnknown),
+			NodeKind::ArrayLiteral { elements } => self.analyze_array_literal(elements, None),
+			NodeKind::TupleLiteral { elements } => self.analyze_tuple_literal(elements, None),
+			NodeKind::Index { array, index } => self.analyze_index(array, index),
+			NodeKind::StructLiteral { name, fields } => self.analyze_struct_literal(name, fields),
+			NodeKind::FieldAccess { expr, field } => self.analyze_field_access(expr, field),
+			NodeKind::MethodCall { expr, method, args } => self.analyze_method_call(expr, method, args),
+			NodeKind::VariantLiteral { enum_name, variant, values } => self.analyze_variant_literal(enum_name, variant, values),
+			// Structs and enums were declared before anything else was analyzed
+			NodeKind::StructDefinition { .. } | NodeKind::EnumDefinition { .. } => Ok(RegisterFormat::Void),
+			NodeKind::Let { name, val_type, value } => self.analyze_let(name, val_type, value),
+			NodeKind::LetTuple { names, val_type, value } => self.analyze_let_tuple(names, val_type, value),
+			NodeKind::Const { name, const_type, value } => self.analyze_global(name, Some(const_type), Some(value), true),
+			NodeKind::If { expr, block, else_block } => self.analyze_if(expr, block, else_block),
+			NodeKind::While { expr, block } => self.analyze_while(expr, block),
+			NodeKind::For { name, start, end, step, block, .. } => self.analyze_for(name, start, end, step, block),
+			NodeKind::ForEach { name, vec, block } => self.analyze_for_each(name, vec, block),
+			NodeKind::Match { expr, arms } => self.analyze_match(expr, arms),
+			NodeKind::Break => self.analyze_loop_control(Identifier::Break),
+			NodeKind::Continue => self.analyze_loop_control(Identifier::Continue),
+			NodeKind::FunctionDefinition { name, parameters, body_block, return_type } => self.analyze_function(name, parameters, body_block, return_type),
+			NodeKind::Return { return_val } => self.analyze_return(return_val),
+			NodeKind::FunctionCall { name, args } => self.analyze_function_call(name, args),
+			NodeKind::Print { exprs } => self.analyze_print(exprs),
+		}.map_err(|error| error.with_span(&node.span))
+	}
+
+	// Analyze a node that must produce a value
+	pub fn analyze_value(&mut self, node: &ASTNode) -> Result<RegisterFormat> {
+		let fmt = self.analyze_node(node)?;
+
+		match fmt {
+			RegisterFormat::Void => Err(Error::ExpressionExpected.with_span(&node.span)),
+			_ if fmt.owns_memory() => self.analyze_move(node).map(|_| fmt),
+			// A number literal used where no format is expected keeps its own, which it must fit in
+			_ if constant::is_number_literal(node) => Self::check_literal_range(node, fmt),
+			_ => Ok(fmt),
+		}
+	}
+
+	// Using a box or vec as a value moves it out of the variable holding it, which can't be used again until it's assigned
+	// Ones reached through a reference can't be moved, since the variable holding them would still own them
+	pub fn analyze_move(&mut self, node: &ASTNode) -> Result<()> {
+		match &node.kind {
+			NodeKind::Literal(Literal::Identifier(Identifier::Symbol(name))) => {
+				if let Some(id) = self.local_id(name) {
+					self.moved.insert(id, name.to_owned());
+				}
+
+				Ok(())
+			},
+			NodeKind::Deref { .. } | NodeKind::Index { .. } | NodeKind::FieldAccess { .. } => Err(Error::MoveOutOfReference.with_span(&node.span)),
+			_ => Ok(()),
+		}
+	}
+
+	// Dereferencing a box and indexing, iterating over or calling a method of a vec use it where it's held, which
+	// must be a variable or be reached through one, since a temporary one would never be freed
+	pub fn analyze_owner_place(&mut self, node: &ASTNode, mutable: bool) -> Result<()> {
+		match self.analyze_lvalue(node, mutable) {
+			Err(error) if matches!(error.inner(), Error::LvalueExpected) => Err(Error::TemporaryOwner.with_span(&node.span)),
+			result => result,
+		}
+	}
+
+	// Register of the local a name refers to, or None if it refers to a global, constant or function
+	pub fn local_id(&self, name: &str) -> Option<String> {
+		match self.local_symbol_table.get(name) {
+			Ok(Symbol::Local { value: LLVMValue::VirtualRegister(reg), .. }) => Some(reg.id().to_owned()),
+			_ => None,
+		}
+	}
+
+	// Analyze a node whose value is used where a value of fmt is expected
+	// Number literals are typed by RegisterFormat::literal_format, including ones in an array, tuple or box literal
+	// used where an array, tuple or box is expected
+	pub fn analyze_value_as(&mut self, node: &ASTNode, fmt: &RegisterFormat) -> Result<RegisterFormat> {
+		match (&node.kind, fmt) {
+			(NodeKind::ArrayLiteral { elements }, RegisterFormat::Array { element, .. }) => {
+				return self.analyze_array_literal(elements, Some(element)).map_err(|error| error.with_span(&node.span));
+			},
+			(NodeKind::TupleLiteral { elements }, RegisterFormat::Tuple { elements: element_fmts }) => {
+				return self.analyze_tuple_literal(elements, Some(element_fmts)).map_err(|error| error.with_span(&node.span));
+			},
+			(NodeKind::BoxNew { value }, RegisterFormat::Box { inner }) => {
+				return self.analyze_box_new(value, Some(inner)).map_err(|error| error.with_span(&node.span));
+			},
+			(NodeKind::VecNew, RegisterFormat::Vec { .. }) => return Ok(fmt.clone()),
+			_ => {},
+		}
+
+		if !constant::is_number_literal(node) {
+			return self.analyze_value(node);
+		}
+
+		let node_fmt = self.analyze_node(node)?;
+		Self::check_literal_range(node, node_fmt.literal_format(Some(fmt)))
+	}
+
+	// Check that an integer literal's value is in the range of the format it takes, giving that format
+	pub fn check_literal_range(node: &ASTNode, fmt: RegisterFormat) -> Result<RegisterFormat> {
+		match (constant::evaluate_literal(node, &fmt), constant::integer_value(node)) {
+			(None, Some(value)) if fmt.is_integer() => Err(Error::IntegerOutOfRange { value, expected: fmt }.with_span(&node.span)),
+			_ => Ok(fmt),
+		}
+	}
+
+	// Analyze operands that must share a format; integer literals take the format of the first other operand
+	pub fn analyze_operands(&mut self, nodes: &[&ASTNode]) -> Result<Vec<RegisterFormat>> {
+		let mut fmts: Vec<Option<RegisterFormat>> = Vec::new();
+		for node in nodes {
+			fmts.push(if constant::is_number_literal(node) { None } else { Some(self.analyze_value(node)?) });
+		}
+
+		let shared = fmts.iter().flatten().next().cloned().unwrap_or(RegisterFormat::Integer);
+		let mut operand_fmts: Vec<RegisterFormat> = Vec::new();
+		for (node, fmt) in nodes.iter().zip(fmts) {
+			operand_fmts.push(match fmt {
+				Some(fmt) => fmt,
+				None => self.analyze_value_as(node, &shared)?,
+			});
+		}
+
+		Ok(operand_fmts)
+	}
+
+	pub fn analyze_literal(&mut self, literal: &Literal) -> Result<RegisterFormat> {
+		match literal {
+			Literal::Integer(_) => Ok(RegisterFormat::Integer),
+			Literal::Float(_) => Ok(RegisterFormat::Float64),
+			Literal::String(_) => Ok(RegisterFormat::String),
+			Literal::Identifier(Identifier::Symbol(name)) => match lookup_symbol(&self.local_symbol_table, &self.global_symbol_table, name)? {
+				Symbol::Function { .. } => Err(Error::ExpressionExpected),
+				_ if self.local_id(name).is_some_and(|id| self.moved.contains_key(&id)) => Err(Error::UseAfterMove { name: name.to_owned() }),
+				symbol => Ok(symbol.value().format()),
+			},
+			Literal::Identifier(i) => Err(Error::TerminalTokenExpected { received_token: None, received_identifier: Some(i.clone()) }),
+		}
+	}
+
+	pub fn analyze_binary(&mut self, token: &Token, left: &ASTNode, right: &ASTNode) -> Result<RegisterFormat> {
+		if let Token::Equals = token {
+			return self.analyze_assign(left, right);
+		}
+
+		let fmts = self.analyze_operands(&[left, right])?;
+		let (left_fmt, right_fmt) = (fmts[0].clone(), fmts[1].clone());
+
+		match token {
+			Token::Asterisk | Token::Minus | Token::Plus | Token::Slash | Token::Percent => {
+				for fmt in [&left_fmt, &right_fmt] {
+					if !fmt.is_number() {
+						return Err(Error::InvalidArithmeticOperand { received: fmt.clone() });
+					}
+				}
+
+				if left_fmt != right_fmt {
+					return Err(Error::MismatchedOperands { left: left_fmt, right: right_fmt });
+				}
+
+				if matches!(token, Token::Slash | Token::Percent) && matches!(self.constant_value(right), Some(Constant::Integer { value: 0, .. })) {
+					return Err(Error::DivisionByZero.with_span(&right.span));
+				}
+
+				Ok(left_fmt)
+			},
+			_ if token.is_comparison() => {
+				if left_fmt.can_compare_to(&right_fmt, token) {
+					Ok(RegisterFormat::Boolean)
+				} else {
+					Err(Error::InvalidComparisonOperands { left: left_fmt, right: right_fmt })
+				}
+			},
+			// Both sides of a logical operator must be booleans
+			_ if token.is_logical() => {
+				RegisterFormat::Boolean.expect(left_fmt).map_err(|error| error.with_span(&left.span))?;
+				RegisterFormat::Boolean.expect(right_fmt).map_err(|error| error.with_span(&right.span))?;
+
+				Ok(RegisterFormat::Boolean)
+			},
+			_ => Err(Error::BinaryOperatorExpected { received: token.clone() }),
+		}
+	}
+
+	// Value of node if it can be computed at compile time
+	pub fn constant_value(&self, node: &ASTNode) -> Option<Constant> {
+		constant::evaluate(node, &|name| named_constant(&self.local_symbol_table, &self.global_symbol_table, name))
+	}
+
+	// Negation applies to signed integers and floats, and logical not to booleans
+	pub fn analyze_unary(&mut self, token: &Token, expr: &ASTNode) -> Result<RegisterFormat> {
+		// A negated literal is checked as a whole, so the smallest value of a signed format can be written
+		let fmt = if constant::is_number_literal(expr) { self.analyze_node(expr)? } else { self.analyze_value(expr)? };
+
+		match token {
+			Token::Minus if fmt.is_signed() || fmt.is_float() => Ok(fmt),
+			Token::Minus => Err(Error::InvalidArithmeticOperand { received: fmt }),
+			Token::Exclamation => RegisterFormat::Boolean.expect(fmt).map(|_| RegisterFormat::Boolean),
+			_ => Err(Error::TerminalTokenExpected { received_token: Some(token.clone()), received_identifier: None }),
+		}
+	}
+
+	// Numbers can be cast to any other number format, and booleans to integers
+	pub fn analyze_cast(&mut self, expr: &ASTNode, cast_type: &Type) -> Result<RegisterFormat> {
+		let received = self.analyze_value(expr)?;
+		let expected = self.types.format_from_type(cast_type)?;
+
+		if received == expected || received.cast_instruction(&expected).is_some() {
+			Ok(expected)
+		} else {
+			Err(Error::InvalidCast { received, expected })
+		}
+	}
+
+	// Anything that can be assigned to can be referenced, and &mut additionally requires that it can be assigned to through the reference
+	pub fn analyze_reference(&mut self, expr: &ASTNode, mutable: bool) -> Result<RegisterFormat> {
+		self.analyze_lvalue(expr, mutable)?;
+		let referent = self.analyze_node(expr)?;
+
+		Ok(RegisterFormat::Reference { referent: Box::new(referent), mutable })
+	}
+
+	// References and boxes can be dereferenced, which doesn't move a box out of its variable
+	pub fn analyze_deref(&mut self, expr: &ASTNode) -> Result<RegisterFormat> {
+		match self.analyze_node(expr)? {
+			RegisterFormat::Reference { referent, .. } => Ok(*referent),
+			RegisterFormat::Box { inner } => self.analyze_owner_place(expr, false).map(|_| *inner),
+			received => Err(Error::InvalidDereference { received }),
+		}
+	}
+
+	// Number literals take the format of inner_fmt if given, as the value a box is created with
+	pub fn analyze_box_new(&mut self, value: &ASTNode, inner_fmt: Option<&RegisterFormat>) -> Result<RegisterFormat> {
+		let inner = match inner_fmt {
+			Some(fmt) => self.analyze_value_as(value, fmt)?,
+			None => self.analyze_value(value)?,
+		};
+
+		let fmt = RegisterFormat::Box { inner: Box::new(inner) };
+		if fmt.holds_owner() {
+			return Err(Error::NestedOwner { container: fmt.to_string() });
+		}
+
+		Ok(fmt)
+	}
+
+	// Every element of an array literal must share a format, which number literals take from element_fmt if given
+	pub fn analyze_array_literal(&mut self, elements: &[ASTNode], element_fmt: Option<&RegisterFormat>) -> Result<RegisterFormat> {
+		let fmts = match element_fmt {
+			Some(fmt) => elements.iter().map(|element| self.analyze_value_as(element, fmt)).collect::<Result<Vec<RegisterFormat>>>()?,
+			None => self.analyze_operands(&elements.iter().collect::<Vec<&ASTNode>>())?,
+		};
+
+		let expected = element_fmt.cloned().unwrap_or_else(|| fmts[0].clone());
+		for (element, fmt) in elements.iter().zip(fmts) {
+			if fmt != expected {
+				return Err(Error::MismatchedElements { expected, received: fmt }.with_span(&element.span));
+			}
+		}
+
+		let fmt = RegisterFormat::Array { element: Box::new(expected), length: elements.len() };
+		if fmt.holds_owner() {
+			return Err(Error::NestedOwner { container: fmt.to_string() });
+		}
+
+		Ok(fmt)
+	}
+
+	// Tuples take the formats of their elements, where number literals take the format given for them if any
+	pub fn analyze_tuple_literal(&mut self, elements: &[ASTNode], element_fmts: Option<&[RegisterFormat]>) -> Result<RegisterFormat> {
+		let mut fmts: Vec<RegisterFormat> = Vec::new();
+		for (i, element) in elements.iter().enumerate() {
+			fmts.push(match element_fmts.and_then(|element_fmts| element_fmts.get(i)) {
+				Some(fmt) => self.analyze_value_as(element, fmt)?,
+				None => self.analyze_value(element)?,
+			});
+		}
+
+		let fmt = RegisterFormat::Tuple { elements: fmts };
+		if fmt.holds_owner() {
+			return Err(Error::NestedOwner { container: fmt.to_string() });
+		}
+
+		Ok(fmt)
+	}
+
+	// Only arrays and vecs can be indexed, and only by integers; constant indexes into arrays must be in bounds,
+	// while indexes into vecs can only be checked at runtime
+	pub fn analyze_index(&mut self, array: &ASTNode, index: &ASTNode) -> Result<RegisterFormat> {
+		let (element, length) = match self.analyze_node(array)? {
+			RegisterFormat::Array { element, length } => (element, Some(length)),
+			RegisterFormat::Vec { element } => {
+				self.analyze_owner_place(array, false)?;
+				(element, None)
+			},
+			received => return Err(Error::InvalidIndexTarget { received }.with_span(&array.span)),
+		};
+
+		let index_fmt = self.analyze_value_as(index, &RegisterFormat::Integer)?;
+		if !index_fmt.is_integer() {
+			return Err(Error::InvalidIndex { received: index_fmt }.with_span(&index.span));
+		}
+
+		if let (Some(Constant::Integer { value, .. }), Some(length)) = (self.constant_value(index), length) {
+			if value < 0 || value >= length as i128 {
+				return Err(Error::IndexOutOfBounds { index: value, length }.with_span(&index.span));
+			}
+		}
+
+		Ok(*element)
+	}
+
+	// Every field of a struct must be given a value of its format exactly once
+	pub fn analyze_struct_literal(&mut self, name: &str, fields: &[(String, ASTNode)]) -> Result<RegisterFormat> {
+		let fmt = self.types.format_from_type(&Type::Named { type_name: name.to_owned() })?;
+		let RegisterFormat::Struct { fields: declared, .. } = &fmt else {
+			return Err(Error::StructExpected { received: fmt });
+		};
+
+		for (i, (field, value)) in fields.iter().enumerate() {
+			if fields[..i].iter().any(|other| other.0 == *field) {
+				return Err(Error::DuplicateField { name: field.to_owned() }.with_span(&value.span));
+			}
+
+			let Some((_, field_fmt)) = fmt.field(field) else {
+				return Err(Error::UnknownField { struct_fmt: fmt.clone(), name: field.to_owned() }.with_span(&value.span));
+			};
+
+			let value_fmt = self.analyze_value_as(value, field_fmt)?;
+			if !value_fmt.can_convert_to(field_fmt) {
+				return Err(Error::InvalidAssignment { received: value_fmt, expected: field_fmt.clone() }.with_span(&value.span));
+			}
+		}
+
+		let missing: Vec<String> = declared.iter()
+			.filter(|declared| !fields.iter().any(|field| field.0 == declared.0))
+			.map(|declared| declared.0.to_owned())
+			.collect();
+		if !missing.is_empty() {
+			return Err(Error::MissingFields { struct_fmt: fmt.clone(), names: missing });
+		}
+
+		Ok(fmt)
+	}
+
+	// Only structs have fields, and only the ones they were declared with
+	pub fn analyze_field_access(&mut self, expr: &ASTNode, field: &str) -> Result<RegisterFormat> {
+		let fmt = self.analyze_value(expr)?;
+		if !matches!(fmt, RegisterFormat::Struct { .. }) {
+			return Err(Error::StructExpected { received: fmt }.with_span(&expr.span));
+		}
+
+		match fmt.field(field) {
+			Some((_, field_fmt)) => Ok(field_fmt.clone()),
+			None => Err(Error::UnknownField { struct_fmt: fmt.clone(), name: field.to_owned() }),
+		}
+	}
+
+	// A variant must be given every value of its payload, in order
+	pub fn analyze_variant_literal(&mut self, enum_name: &str, variant: &str, values: &[ASTNode]) -> Result<RegisterFormat> {
+		let fmt = self.types.format_from_type(&Type::Named { type_name: enum_name.to_owned() })?;
+		if !matches!(fmt, RegisterFormat::Enum { .. }) {
+			return Err(Error::EnumExpected { received: fmt });
+		}
+
+		let Some((_, payload)) = fmt.variant(variant) else {
+			return Err(Error::UnknownVariant { enum_fmt: fmt.clone(), name: variant.to_owned() });
+		};
+		let payload = payload.clone();
+
+		// Number literals take the format of the payload value they're given for
+		let mut value_fmts: Vec<RegisterFormat> = Vec::new();
+		for (i, value) in values.iter().enumerate() {
+			value_fmts.push(match payload.get(i) {
+				Some(expected) => self.analyze_value_as(value, expected)?,
+				None => self.analyze_value(value)?,
+			});
+		}
+
+		let matches = payload.len() == value_fmts.len()
+			&& payload.iter().zip(value_fmts.iter()).all(|(expected, received)| received.can_convert_to(expected));
+		if !matches {
+			return Err(Error::InvalidPayload { variant: format!("{enum_name}::{variant}"), expected: payload, received: value_fmts });
+		}
+
+		Ok(fmt)
+	}
+
+	// Only variables and elements of variables can be assigned to, and only with values of their own format
+	pub fn analyze_assign(&mut self, left: &ASTNode, right: &ASTNode) -> Result<RegisterFormat> {
+		self.analyze_lvalue(left, true)?;
+
+		// A variable whose box was moved out can be assigned a new one, so it isn't used as a value here
+		let assigned = match &left.kind {
+			NodeKind::Literal(Literal::Identifier(Identifier::Symbol(name))) => Some(name),
+			_ => None,
+		};
+		let left_fmt = match assigned {
+			Some(name) => lookup_symbol(&self.local_symbol_table, &self.global_symbol_table, name)?.value().format(),
+			None => self.analyze_node(left)?,
+		};
+		let right_fmt = self.analyze_value_as(right, &left_fmt)?;
+		left_fmt.expect_convertible(right_fmt)?;
+
+		if let Some(id) = assigned.and_then(|name| self.local_id(name)) {
+			self.moved.remove(&id);
+			if let Some(referent) = self.local_referent(right) {
+				self.borrows.insert(id, referent);
+			}
+		}
+
+		Ok(left_fmt)
+	}
+
+	// Check that node names storage, which must be assignable if mutable; storage reached through a reference
+	// is only assignable if the reference is mutable
+	pub fn analyze_lvalue(&mut self, node: &ASTNode, mutable: bool) -> Result<()> {
+		match &node.kind {
+			NodeKind::Literal(Literal::Identifier(Identifier::Symbol(name))) => {
+				if named_constant(&self.local_symbol_table, &self.global_symbol_table, name).is_some() {
+					return Err(Error::ConstantAssignment { name: name.to_owned() }.with_span(&node.span));
+				}
+
+				Ok(())
+			},
+			NodeKind::Index { array, .. } => self.analyze_lvalue(array, mutable),
+			NodeKind::FieldAccess { expr, .. } => self.analyze_lvalue(expr, mutable),
+			NodeKind::Deref { expr } => match self.analyze_node(expr)? {
+				received @ RegisterFormat::Reference { mutable: false, .. } if mutable => Err(Error::ImmutableReference { received }.with_span(&node.span)),
+				_ => Ok(()),
+			},
+			_ => Err(Error::LvalueExpected.with_span(&node.span)),
+		}
+	}
+
+	// A variable may shadow one from an enclosing block, but not one declared in the same block
+	pub fn analyze_let(&mut self, name: &str, val_type: &Option<Type>, value: &Option<Box<ASTNode>>) -> Result<RegisterFormat> {
+		if self.local_symbol_table.is_declared_in_scope(name) {
+			return Err(Error::SymbolDeclared { name: name.to_owned() });
+		}
+
+		// Declare the variable even if its value is invalid, so later uses don't report it as undefined
+		let declared_fmt = match val_type {
+			Some(v) => Some(self.types.format_from_type(v)?),
+			None => None,
+		};
+		let assigned_fmt = value.as_ref().map(|val| match &declared_fmt {
+			Some(fmt) => self.analyze_value_as(val, fmt),
+			None => self.analyze_value(val),
+		});
+		let reg_fmt = match (&declared_fmt, &assigned_fmt) {
+			(Some(fmt), _) => fmt.clone(),
+			(None, Some(Ok(fmt))) => fmt.clone(),
+			_ => RegisterFormat::Integer,
+		};
+		let referent = value.as_deref().and_then(|val| self.local_referent(val));
+		let (symbol, reg) = self.local_symbol_table.create_local(name, &reg_fmt);
+		self.local_symbol_table.insert(symbol);
+
+		if let Some(referent) = referent {
+			self.borrows.insert(reg.id().to_owned(), referent);
+		}
+
+		// A box or vec variable declared without a value holds nothing until it's assigned, just like one moved out of
+		if reg_fmt.owns_memory() && value.is_none() {
+			self.moved.insert(reg.id().to_owned(), name.to_owned());
+		}
+
+		if let Some(assigned_fmt) = assigned_fmt {
+			let assigned_fmt = assigned_fmt?;
+			if !assigned_fmt.can_convert_to(&reg_fmt) {
+				return Err(Error::InvalidAssignment { received: assigned_fmt, expected: reg_fmt });
+			}
+		}
+
+		Ok(RegisterFormat::Void)
+	}
+
+	// A tuple is destructured into a variable for each element that isn't bound to '_', which takes its format
+	pub fn analyze_let_tuple(&mut self, names: &[Option<String>], val_type: &Option<Type>, value: &ASTNode) -> Result<RegisterFormat> {
+		let declared_fmt = val_type.as_ref().map(|v| self.types.format_from_type(v)).transpose()?;
+		let assigned_fmt = match &declared_fmt {
+			Some(fmt) => self.analyze_value_as(value, fmt),
+			None => self.analyze_value(value),
+		};
+
+		// Declare the variables even if the value is invalid, so later uses don't report them as undefined
+		let element_fmts = match (&declared_fmt, &assigned_fmt) {
+			(Some(RegisterFormat::Tuple { elements }), _) | (None, Ok(RegisterFormat::Tuple { elements })) => elements.clone(),
+			_ => Vec::new(),
+		};
+		for (i, name) in names.iter().enumerate() {
+			let Some(name) = name else {
+				continue;
+			};
+
+			if self.local_symbol_table.is_declared_in_scope(name) {
+				return Err(Error::SymbolDeclared { name: name.to_owned() });
+			}
+
+			let (symbol, _) = self.local_symbol_table.create_local(name, element_fmts.get(i).unwrap_or(&RegisterFormat::Integer));
+			self.local_symbol_table.insert(symbol);
+		}
+
+		let assigned_fmt = assigned_fmt?;
+		if let Some(declared_fmt) = &declared_fmt {
+			if !assigned_fmt.can_convert_to(declared_fmt) {
+				return Err(Error::InvalidAssignment { received: assigned_fmt, expected: declared_fmt.clone() });
+			}
+		}
+
+		match assigned_fmt {
+			RegisterFormat::Tuple { elements } if elements.len() == names.len() => Ok(RegisterFormat::Void),
+			received => Err(Error::InvalidDestructure { count: names.len(), received }),
+		}
+	}
+
+	// Globals are initialized before the program runs, so their values must be known at compile time
+	// Constants are declared the same way, but can't be assigned to
+	pub fn analyze_global(&mut self, name: &str, val_type: Option<&Type>, value: Option<&ASTNode>, constant: bool) -> Result<RegisterFormat> {
+		Self::check_global_name(name)?;
+		if self.global_symbol_table.get(name).is_ok() {
+			return Err(Error::SymbolDeclared { name: name.to_owned() });
+		}
+
+		// Declare the global even if its value is invalid, so later uses don't report it as undefined
+		let declared_fmt = val_type.map(|v| self.types.format_from_type(v)).transpose()?;
+		let assigned_fmt = value.map(|val| match &declared_fmt {
+			Some(fmt) => self.analyze_value_as(val, fmt),
+			None => self.analyze_value(val),
+		});
+		let initial = value.and_then(|val| match &declared_fmt {
+			Some(fmt) if constant::is_number_literal(val) => constant::evaluate_literal(val, fmt),
+			_ => self.constant_value(val),
+		});
+		let reg_fmt = match (&declared_fmt, &assigned_fmt) {
+			(Some(fmt), _) => fmt.clone(),
+			(None, Some(Ok(fmt))) => fmt.clone(),
+			_ => RegisterFormat::Integer,
+		};
+
+		if constant {
+			let placeholder = initial.clone().or_else(|| Constant::zero(&reg_fmt)).unwrap_or(Constant::Integer { value: 0, format: RegisterFormat::Integer });
+			let symbol = self.global_symbol_table.create_constant(name, &placeholder);
+			self.global_symbol_table.insert(symbol);
+		} else {
+			let (symbol, _) = self.global_symbol_table.create_global(name, &reg_fmt);
+			self.global_symbol_table.insert(symbol);
+		}
+
+		if Constant::zero(&reg_fmt).is_none() {
+			return Err(Error::GlobalTypeUnsupported { received: reg_fmt });
+		}
+
+		if let (Some(assigned_fmt), Some(value)) = (assigned_fmt, value) {
+			let assigned_fmt = assigned_fmt?;
+			if !assigned_fmt.can_convert_to(&reg_fmt) {
+				return Err(Error::InvalidAssignment { received: assigned_fmt, expected: reg_fmt });
+			}
+
+			if initial.is_none() {
+				return Err(Error::ConstantExpected.with_span(&value.span));
+			}
+		}
+
+		Ok(RegisterFormat::Void)
+	}
+
+	// Conditions of if and while statements must be booleans
+	pub fn analyze_condition(&mut self, expr: &ASTNode) {
+		let result = self.analyze_value(expr)
+			.and_then(|fmt| RegisterFormat::Boolean.expect(fmt).map_err(|error| error.with_span(&expr.span)));
+
+		if let Err(error) = result {
+			self.diagnostics.push(error);
+		}
+	}
+
+	pub fn analyze_if(&mut self, expr: &ASTNode, block: &[ASTNode], else_block: &Option<Vec<ASTNode>>) -> Result<RegisterFormat> {
+		self.analyze_condition(expr);
+
+		let before = self.moved.clone();
+		let mut after = self.analyze_branch(block, &before);
+		if let Some(else_block) = else_block {
+			after.append(&mut self.analyze_branch(else_block, &before));
+		}
+		self.moved = after;
+
+		Ok(RegisterFormat::Void)
+	}
+
+	// Analyze one of the blocks an if or match chooses between, starting from the values moved before the choice
+	// Gives the values moved once the block is done, which are none if it leaves with a return, break or continue;
+	// afterwards a value counts as moved if any of the blocks moved it
+	pub fn analyze_branch(&mut self, block: &[ASTNode], before: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+		self.moved = before.clone();
+		self.analyze_block(block);
+
+		if Self::always_leaves(block) {
+			before.clone()
+		} else {
+			std::mem::take(&mut self.moved)
+		}
+	}
+
+	// Analyze the body of a loop; afterwards a value counts as moved if it was moved before the loop, by the end of
+	// an iteration or before a break
+	// A box or vec moved out of a variable declared outside the loop by the end of an iteration or before a continue
+	// would be moved out again by the next iteration, unless the body assigns the variable a new one first
+	pub fn analyze_loop_body(&mut self, block: &[ASTNode]) -> Result<()> {
+		let before = self.moved.clone();
+		self.loop_moves.push((BTreeMap::new(), BTreeMap::new()));
+		self.analyze_block(block);
+		let (mut breaks, mut repeated) = self.loop_moves.pop().unwrap_or_default();
+
+		if !Self::always_leaves(block) {
+			repeated.append(&mut self.moved);
+		}
+		let moved_again = repeated.iter()
+			.find(|(id, name)| !before.contains_key(*id) && self.local_id(name).as_ref() == Some(*id))
+			.map(|(_, name)| name.to_owned());
+
+		self.moved = before;
+		self.moved.append(&mut repeated);
+		self.moved.append(&mut breaks);
+
+		match moved_again {
+			Some(name) => Err(Error::MovedInLoop { name }),
+			None => Ok(()),
+		}
+	}
+
+	pub fn analyze_while(&mut self, expr: &ASTNode, block: &[ASTNode]) -> Result<RegisterFormat> {
+		self.analyze_condition(expr);
+		self.analyze_loop_body(block)?;
+
+		Ok(RegisterFormat::Void)
+	}
+
+	// Bounds and step of a for loop must be integers of the same format, which the loop variable takes
+	// The loop variable is only visible in the body
+	pub fn analyze_for(&mut self, name: &str, start: &ASTNode, end: &ASTNode, step: &Option<Box<ASTNode>>, block: &[ASTNode]) -> Result<RegisterFormat> {
+		let bounds: Vec<&ASTNode> = [Some(start), Some(end), step.as_deref()].into_iter().flatten().collect();
+		let loop_fmt = match self.analyze_operands(&bounds) {
+			Ok(fmts) => {
+				let loop_fmt = if fmts[0].is_integer() { fmts[0].clone() } else { RegisterFormat::Integer };
+				for (bound, fmt) in bounds.iter().zip(fmts) {
+					if let Err(error) = loop_fmt.expect(fmt) {
+						self.diagnostics.push(error.with_span(&bound.span));
+					}
+				}
+
+				loop_fmt
+			},
+			Err(error) => {
+				self.diagnostics.push(error);
+				RegisterFormat::Integer
+			},
+		};
+
+		if let Some(step) = step {
+			if let Some(Constant::Integer { value: value @ ..=0, .. }) = self.constant_value(step) {
+				self.diagnostics.push(Error::NonPositiveStep { step: value }.with_span(&step.span));
+			}
+		}
+
+		self.local_symbol_table.push_scope();
+		let (symbol, _) = self.local_symbol_table.create_local(name, &loop_fmt);
+		self.local_symbol_table.insert(symbol);
+		let result = self.analyze_loop_body(block);
+		self.local_symbol_table.pop_scope();
+		result?;
+
+		Ok(RegisterFormat::Void)
+	}
+
+	// A for loop can go over the elements of a vec held by a variable or one a reference points to, binding a copy
+	// of each element to the loop variable in the body
+	pub fn analyze_for_each(&mut self, name: &str, vec: &ASTNode, block: &[ASTNode]) -> Result<RegisterFormat> {
+		let fmt = self.analyze_node(vec)?;
+		let iterated = match &fmt {
+			RegisterFormat::Reference { referent, .. } => referent.as_ref(),
+			_ => &fmt,
+		};
+		let RegisterFormat::Vec { element } = iterated else {
+			return Err(Error::InvalidIterable { received: fmt.clone() }.with_span(&vec.span));
+		};
+		let element_fmt = *element.clone();
+		if !matches!(fmt, RegisterFormat::Reference { .. }) {
+			self.analyze_owner_place(vec, false)?;
+		}
+
+		self.local_symbol_table.push_scope();
+		let (symbol, _) = self.local_symbol_table.create_local(name, &element_fmt);
+		self.local_symbol_table.insert(symbol);
+		let result = self.analyze_loop_body(block);
+		self.local_symbol_table.pop_scope();
+		result?;
+
+		Ok(RegisterFormat::Void)
+	}
+
+	// Enums are matched against their variants and integers against integer literals, either of which may be
+	// followed by a '_' arm; every value must be covered, and every arm must match a value no earlier arm does
+	// Names bound by a pattern are only visible in the arm's block
+	pub fn analyze_match(&mut self, expr: &ASTNode, arms: &[MatchArm]) -> Result<RegisterFormat> {
+		let fmt = self.analyze_value(expr)?;
+		if !matches!(fmt, RegisterFormat::Enum { .. }) && !fmt.is_integer() {
+			return Err(Error::InvalidMatchTarget { received: fmt }.with_span(&expr.span));
+		}
+
+		let before = self.moved.clone();
+		let mut after = before.clone();
+		for (i, arm) in arms.iter().enumerate() {
+			if arms[..i].iter().any(|earlier| earlier.pattern.covers(&arm.pattern)) {
+				self.diagnostics.push(Error::UnreachablePattern { pattern: arm.pattern.clone() }.with_span(&arm.span));
+			}
+
+			// Blocks of invalid patterns are skipped, since they would report their bindings as undefined
+			let bindings = match self.analyze_pattern(&arm.pattern, &fmt) {
+				Ok(bindings) => bindings,
+				Err(error) => {
+					self.diagnostics.push(error.with_span(&arm.span));
+					continue;
+				},
+			};
+
+			self.local_symbol_table.push_scope();
+			for (name, binding_fmt) in bindings {
+				if self.local_symbol_table.is_declared_in_scope(&name) {
+					self.diagnostics.push(Error::SymbolDeclared { name: name.to_owned() }.with_span(&arm.span));
+				}
+
+				let (symbol, _) = self.local_symbol_table.create_local(&name, &binding_fmt);
+				self.local_symbol_table.insert(symbol);
+			}
+			after.append(&mut self.analyze_branch(&arm.block, &before));
+			self.local_symbol_table.pop_scope();
+		}
+		self.moved = after;
+
+		// Integers can only be covered by a '_' arm
+		let missing: Vec<String> = match &fmt {
+			RegisterFormat::Enum { name, variants } => variants.iter()
+				.map(|variant| Pattern::Variant { enum_name: name.to_owned(), variant: variant.0.to_owned(), bindings: Vec::new() })
+				.filter(|pattern| !arms.iter().any(|arm| arm.pattern.covers(pattern)))
+				.map(|pattern| pattern.to_string())
+				.collect(),
+			_ if arms.iter().any(|arm| matches!(arm.pattern, Pattern::Wildcard)) => Vec::new(),
+			_ => vec![Pattern::Wildcard.to_string()],
+		};
+		if !missing.is_empty() {
+			return Err(Error::NonExhaustiveMatch { missing });
+		}
+
+		Ok(RegisterFormat::Void)
+	}
+
+	// Check that pattern can match a value of fmt, giving the names it binds along with their formats
+	pub fn analyze_pattern(&self, pattern: &Pattern, fmt: &RegisterFormat) -> Result<Vec<(String, RegisterFormat)>> {
+		match (pattern, fmt) {
+			(Pattern::Wildcard, _) => Ok(Vec::new()),
+			(Pattern::Integer(value), _) if fmt.is_integer() => {
+				if fmt.can_hold(*value) {
+					Ok(Vec::new())
+				} else {
+					Err(Error::IntegerOutOfRange { value: *value, expected: fmt.clone() })
+				}
+			},
+			(Pattern::Variant { enum_name, variant, bindings }, RegisterFormat::Enum { name, .. }) if enum_name == name => {
+				let Some((_, payload)) = fmt.variant(variant) else {
+					return Err(Error::UnknownVariant { enum_fmt: fmt.clone(), name: variant.to_owned() });
+				};
+
+				// Every value of the payload must be bound, even if only to '_'
+				if payload.len() != bindings.len() {
+					return Err(Error::InvalidPattern { pattern: pattern.clone(), received: fmt.clone() });
+				}
+
+				Ok(bindings.iter().zip(payload)
+					.filter_map(|(binding, binding_fmt)| binding.as_ref().map(|name| (name.to_owned(), binding_fmt.clone())))
+					.collect())
+			},
+			_ => Err(Error::InvalidPattern { pattern: pattern.clone(), received: fmt.clone() }),
+		}
+	}
+
+	// break and continue must be inside a loop, which records the values moved before them
+	pub fn analyze_loop_control(&mut self, keyword: Identifier) -> Result<RegisterFormat> {
+		let Some((breaks, continues)) = self.loop_moves.last_mut() else {
+			return Err(Error::LoopControlOutsideLoop { keyword });
+		};
+
+		let exits = if let Identifier::Break = keyword { breaks } else { continues };
+		exits.extend(self.moved.clone());
+
+		Ok(RegisterFormat::Void)
+	}
+
+	// Add a struct's format to the type registry; field types must be declared before the struct
+	// Fields that are invalid are recorded and left out, so uses of the struct can still be checked
+	pub fn declare_struct(&mut self, name: &str, fields: &[StructField]) -> Result<()> {
+		let mut field_fmts: Vec<(String, RegisterFormat)> = Vec::new();
+		for field in fields {
+			if field_fmts.iter().any(|declared| declared.0 == field.name) {
+				self.diagnostics.push(Error::DuplicateField { name: field.name.to_owned() }.with_span(&field.span));
+				continue;
+			}
+
+			match self.types.format_from_type(&field.field_type) {
+				Ok(fmt) if fmt.owns_memory() => self.diagnostics.push(Error::NestedOwner { container: format!("struct {name}") }.with_span(&field.span)),
+				Ok(fmt) => field_fmts.push((field.name.to_owned(), fmt)),
+				Err(error) => self.diagnostics.push(error.with_span(&field.span)),
+			}
+		}
+
+		self.types.declare(name, RegisterFormat::Struct { name: name.to_owned(), fields: field_fmts })
+	}
+
+	// Add an enum's format to the type registry; payload types must be declared before the enum
+	// Variants that are invalid are recorded and left out, so uses of the enum can still be checked
+	pub fn declare_enum(&mut self, name: &str, variants: &[EnumVariant]) -> Result<()> {
+		let mut variant_fmts: Vec<(String, Vec<RegisterFormat>)> = Vec::new();
+		for variant in variants {
+			if variant_fmts.iter().any(|declared| declared.0 == variant.name) {
+				self.diagnostics.push(Error::DuplicateVariant { name: variant.name.to_owned() }.with_span(&variant.span));
+				continue;
+			}
+
+			match variant.payload.iter().map(|payload_type| self.types.format_from_type(payload_type)).collect::<Result<Vec<RegisterFormat>>>() {
+				Ok(payload) if payload.iter().any(RegisterFormat::owns_memory) => {
+					self.diagnostics.push(Error::NestedOwner { container: format!("enum {name}") }.with_span(&variant.span));
+				},
+				Ok(payload) => variant_fmts.push((variant.name.to_owned(), payload)),
+				Err(error) => self.diagnostics.push(error.with_span(&variant.span)),
+			}
+		}
+
+		self.types.declare(name, RegisterFormat::Enum { name: name.to_owned(), variants: variant_fmts })
+	}
+
+	// Functions and globals share a namespace with the functions the runtime uses
+	pub fn check_global_name(name: &str) -> Result<()> {
+		if RUNTIME_SYMBOLS.contains(&name) {
+			return Err(Error::ReservedName { name: name.to_owned() });
+		}
+
+		Ok(())
+	}
+
+	// Add a function's signature to the global symbol table
+	pub fn declare_function(&mut self, name: &str, parameters: &[FunctionParameter], return_type: &Type) -> Result<()> {
+		Self::check_global_name(name)?;
+		if self.global_symbol_table.get(name).is_ok() {
+			return Err(Error::SymbolDeclared { name: name.to_owned() });
+		}
+
+		let signature = self.function_signature(parameters, return_type)?;
+		let (func_symbol, _) = self.global_symbol_table.create_function(name, &signature);
+		self.global_symbol_table.insert(func_symbol);
+
+		Ok(())
+	}
+
+	// Resolve the formats of a function's parameters and return type
+	pub fn function_signature(&self, parameters: &[FunctionParameter], return_type: &Type) -> Result<FunctionSignature> {
+		let return_fmt = self.types.format_from_type(return_type)?;
+		let mut param_fmts: Vec<RegisterFormat> = Vec::new();
+		for param in parameters {
+			param_fmts.push(self.types.format_from_type(&param.param_type).map_err(|error| error.with_span(&param.span))?);
+		}
+
+		Ok(FunctionSignature::new(&param_fmts, return_fmt))
+	}
+
+	pub fn analyze_function(&mut self, name: &str, parameters: &[FunctionParameter], body_block: &[ASTNode], return_type: &Type) -> Result<RegisterFormat> {
+		// Errors in the signature were already reported when the function was declared
+		let Ok(signature) = self.function_signature(parameters, return_type) else {
+			return Ok(RegisterFormat::Void);
+		};
+		let return_fmt = signature.return_fmt().clone();
+
+		for (param, fmt) in parameters.iter().zip(signature.params().iter()) {
+			if self.local_symbol_table.is_declared_in_scope(&param.name) {
+				self.diagnostics.push(Error::SymbolDeclared { name: param.name.to_owned() }.with_span(&param.span));
+			}
+
+			let (symbol, _) = self.local_symbol_table.create_local(&param.name, fmt);
+			self.local_symbol_table.insert(symbol);
+		}
+
+		self.return_fmt = return_fmt.clone();
+		self.analyze_block(body_block);
+		self.local_symbol_table.clear();
+		self.moved.clear();
+		self.borrows.clear();
+
+		if return_fmt != RegisterFormat::Void && !Self::always_returns(body_block) {
+			return Err(Error::MissingReturn { name: name.to_owned(), expected: return_fmt });
+		}
+
+		Ok(RegisterFormat::Void)
+	}
+
+	// Whether every path through block ends in a return statement
+	pub fn always_returns(block: &[ASTNode]) -> bool {
+		block.iter().any(|statement| match &statement.kind {
+			NodeKind::Return { .. } => true,
+			NodeKind::If { block, else_block: Some(else_block), .. } => Self::always_returns(block) && Self::always_returns(else_block),
+			// Analysis makes sure some arm is taken for every value
+			NodeKind::Match { arms, .. } => arms.iter().all(|arm| Self::always_returns(&arm.block)),
+			_ => false,
+		})
+	}
+
+	// Whether every path through block ends in a return, break or continue statement, so none reaches its end
+	pub fn always_leaves(block: &[ASTNode]) -> bool {
+		block.iter().any(|statement| match &statement.kind {
+			NodeKind::Return { .. } | NodeKind::Break | NodeKind::Continue => true,
+			NodeKind::If { block, else_block: Some(else_block), .. } => Self::always_leaves(block) && Self::always_leaves(else_block),
+			NodeKind::Match { arms, .. } => arms.iter().all(|arm| Self::always_leaves(&arm.block)),
+			_ => false,
+		})
+	}
+
+	pub fn analyze_return(&mut self, return_val: &Option<Box<ASTNode>>) -> Result<RegisterFormat> {
+		let fmt = match return_val {
+			Some(val) => self.analyze_value_as(val, &self.return_fmt.clone())?,
+			None => RegisterFormat::Void,
+		};
+		self.return_fmt.expect_convertible(fmt)?;
+
+		// Locals only live until the function returns, so references to them can't be returned, even as part of a tuple
+		let returned: Vec<&ASTNode> = match return_val.as_deref() {
+			Some(ASTNode { kind: NodeKind::TupleLiteral { elements }, .. }) => elements.iter().collect(),
+			Some(val) => vec![val],
+			None => Vec::new(),
+		};
+		for val in returned {
+			if let Some(name) = self.local_referent(val) {
+				return Err(Error::LocalReferenceReturned { name }.with_span(&val.span));
+			}
+		}
+
+		Ok(RegisterFormat::Void)
+	}
+
+	// Name of the local variable whose storage node is part of, or None if it's a global or reached through a reference
+	// to storage that outlives the function; the value in a local's box is freed along with the local
+	pub fn local_root(&self, node: &ASTNode) -> Option<String> {
+		match &node.kind {
+			NodeKind::Literal(Literal::Identifier(Identifier::Symbol(name))) => self.local_symbol_table.get(name).ok().map(|_| name.to_owned()),
+			NodeKind::Index { array, .. } => self.local_root(array),
+			NodeKind::FieldAccess { expr, .. } => self.local_root(expr),
+			NodeKind::Deref { expr } => match &expr.kind {
+				NodeKind::Literal(Literal::Identifier(Identifier::Symbol(name)))
+					if matches!(self.local_symbol_table.get(name).map(|symbol| symbol.value().format()), Ok(RegisterFormat::Box { .. })) => self.local_root(expr),
+				_ => self.local_referent(expr),
+			},
+			_ => None,
+		}
+	}
+
+	// Name of the local variable a reference value may point into, whether it's taken there or held by a local
+	// that was given one
+	pub fn local_referent(&self, node: &ASTNode) -> Option<String> {
+		match &node.kind {
+			NodeKind::Reference { expr, .. } => self.local_root(expr),
+			NodeKind::Literal(Literal::Identifier(Identifier::Symbol(name))) => self.local_id(name).and_then(|id| self.borrows.get(&id).cloned()),
+			_ => None,
+		}
+	}
+
+	pub fn analyze_function_call(&mut self, name: &str, args: &[ASTNode]) -> Result<RegisterFormat> {
+		let RegisterFormat::Function { signature } = self.global_symbol_table.get(name)?.value().format() else {
+			return Err(Error::ExpressionExpected);
+		};
+
+		self.analyze_args(&signature, args)?;
+		Ok(signature.return_fmt().clone())
+	}
+
+	// Methods are called on a vec where it's held, which must be assignable for methods that change it, or through a
+	// reference to one, which must be mutable for methods that change it
+	pub fn analyze_method_call(&mut self, expr: &ASTNode, method: &str, args: &[ASTNode]) -> Result<RegisterFormat> {
+		let fmt = self.analyze_node(expr)?;
+		let receiver = match &fmt {
+			RegisterFormat::Reference { referent, .. } => referent.as_ref(),
+			_ => &fmt,
+		};
+		let Some((signature, mutates)) = receiver.method(method) else {
+			return Err(Error::UnknownMethod { received: fmt, name: method.to_owned() });
+		};
+
+		match &fmt {
+			RegisterFormat::Reference { mutable: false, .. } if mutates => return Err(Error::ImmutableReference { received: fmt }.with_span(&expr.span)),
+			RegisterFormat::Reference { .. } => {},
+			_ => self.analyze_owner_place(expr, mutates)?,
+		}
+
+		self.analyze_args(&signature, args)?;
+		Ok(signature.return_fmt().clone())
+	}
+
+	// Check the arguments of a call against the signature of the function or method called
+	pub fn analyze_args(&mut self, signature: &FunctionSignature, args: &[ASTNode]) -> Result<()> {
+		// Integer literals take the format of the parameter they're passed to
+		let mut arg_fmts: Vec<RegisterFormat> = Vec::new();
+		for (i, arg) in args.iter().enumerate() {
+			arg_fmts.push(match signature.params().get(i) {
+				Some(param) => self.analyze_value_as(arg, param)?,
+				None => self.analyze_value(arg)?,
+			});
+		}
+
+		// Argument count and formats must match the parameters exactly
+		let matches = signature.params().len() == arg_fmts.len()
+			&& signature.params().iter().zip(arg_fmts.iter()).all(|(param, arg)| arg.can_convert_to(param));
+		if !matches {
+			return Err(Error::ArgumentMismatch { expected: signature.clone(), received: arg_fmts });
+		}
+
+		Ok(())
+	}
+
+	pub fn analyze_print(&mut self, exprs: &[ASTNode]) -> Result<RegisterFormat> {
+		for expr in exprs {
+			let fmt = self.analyze_value(expr)?;
+			if !fmt.is_number() && !matches!(fmt, RegisterFormat::Boolean | RegisterFormat::String) {
+				return Err(Error::UnprintableValue { received: fmt }.with_span(&expr.span));
+			}
+		}
+
+		Ok(RegisterFormat::Void)
+	}
+}
\ No newline at end of file
@@ -1,5 +1,7 @@
+use std::result;
+
 use clap::Parser;
-use crate::error::*;
+use crate::error::diagnostic::Diagnostics;
 
 #[derive(Debug, Parser)]
 #[command(author, version)]
@@ -11,6 +13,14 @@ pub struct Args {
 	// Enable debug mode
 	#[arg(short, long)]
 	debug: bool,
+
+	// Abort with a message instead of dividing by zero or overflowing a division at runtime
+	#[arg(long)]
+	checked_arithmetic: bool,
+
+	// Abort with the index and length instead of indexing an array or vec out of bounds at runtime
+	#[arg(long)]
+	checked_indexing: bool,
 }
 
 impl Args {
@@ -22,24 +32,57 @@ impl Args {
 	pub fn debug(&self) -> bool {
 		self.debug
 	}
+
+	pub fn checked_arithmetic(&self) -> bool {
+		self.checked_arithmetic
+	}
+
+	pub fn checked_indexing(&self) -> bool {
+		self.checked_indexing
+	}
 }
 
 pub fn parse_args() -> Args {
 	Args::parse()
 }
 
-pub fn compile(args: &Args) -> Result<()> {
-	for (_, filename) in args.input_files().iter().enumerate() {
+// Compile every input file, gathering the errors of all of them
+pub fn compile(args: &Args) -> result::Result<(), Diagnostics> {
+	let mut diagnostics = Diagnostics::new();
+
+	for filename in args.input_files().iter() {
 		if args.debug() {
 			println!("Compiling {}.", filename);
 		}
 
-		let scanner = crate::scanning::Scanner::open_file(filename.to_owned())?;
-		let mut parser = crate::parsing::Parser::new(scanner)?;
+		if let Err(errors) = compile_file(filename, args) {
+			diagnostics.extend(errors);
+		}
+	}
+
+	if diagnostics.is_empty() { Ok(()) } else { Err(diagnostics) }
+}
 
-		let mut generator = crate::generating::Generator::from_filename(filename.to_owned() + ".ll")?;
-		generator.generate(&mut parser)?;
+// Compile a single file; all syntax and semantic errors are reported before any output is written
+pub fn compile_file(filename: &str, args: &Args) -> result::Result<(), Diagnostics> {
+	let scanner = crate::scanning::Scanner::open_file(filename.to_owned())?;
+	let mut parser = crate::parsing::Parser::new(scanner)?;
+
+	let program = parser.parse_program();
+	if !parser.diagnostics().is_empty() {
+		return Err(parser.take_diagnostics());
+	}
+
+	let mut analyzer = crate::analyzing::Analyzer::new();
+	analyzer.analyze(&program);
+	if !analyzer.diagnostics().is_empty() {
+		return Err(analyzer.take_diagnostics());
 	}
 
+	let mut generator = crate::generating::Generator::from_filename(filename.to_owned() + ".ll")?;
+	generator.set_checked_arithmetic(args.checked_arithmetic());
+	generator.set_checked_indexing(args.checked_indexing());
+	generator.generate(&program)?;
+
 	Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+
+use super::Error;
+
+// Renders an error along with the source line it occurred on, e.g.
+//
+// error: SymbolUndefined: 'y'
+//  --> main.rua:3:8
+//   |
+// 3 |     print y;
+//   |           ^
+//   = help: `y` must be declared before it is used
+pub struct Diagnostic<'a> {
+	error: &'a Error,
+	source: Option<String>,
+}
+
+impl<'a> Diagnostic<'a> {
+	// Create a diagnostic, reading the source line from the file the error points at
+	pub fn new(error: &'a Error) -> Self {
+		let source = error.span().and_then(|span| fs::read_to_string(span.filename()).ok());
+
+		Self {
+			error,
+			source,
+		}
+	}
+
+	// Create a diagnostic with already loaded source text
+	pub fn with_source(error: &'a Error, source: Option<String>) -> Self {
+		Self {
+			error,
+			source,
+		}
+	}
+
+	pub fn error(&self) -> &Error {
+		self.error
+	}
+}
+
+impl fmt::Display for Diagnostic<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "error: {}", self.error.inner())?;
+
+		let Some(span) = self.error.span() else {
+			if let Some(help) = self.error.help() {
+				writeln!(f, "  = help: {help}")?;
+			}
+
+			return Ok(());
+		};
+
+		// Width of the line number gutter so that all rows line up
+		let line_no = span.start().line;
+		let gutter = " ".repeat(line_no.to_string().len());
+		writeln!(f, "{gutter}--> {span}")?;
+
+		if let Some(line) = self.source.as_ref().and_then(|source| source.lines().nth(line_no - 1)) {
+			// Underline until end of span, or end of line if span continues onto later lines
+			let start = span.start().column - 1;
+			let end = if span.end().line == line_no { span.end().column - 1 } else { line.chars().count() };
+			let width = end.saturating_sub(start).max(1);
+
+			// Keep tabs in the padding so the caret lines up with the source as displayed
+			let padding: String = line.chars().take(start).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+
+			writeln!(f, "{gutter} |")?;
+			writeln!(f, "{line_no} | {line}")?;
+			writeln!(f, "{gutter} | {padding}{}", "^".repeat(width))?;
+		}
+
+		if let Some(help) = self.error.help() {
+			writeln!(f, "{gutter} = help: {help}")?;
+		}
+
+		Ok(())
+	}
+}
+// Collection of errors gathered while compiling, so several can be reported at once
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+	errors: Vec<Error>,
+}
+
+impl Diagnostics {
+	pub fn new() -> Self {
+		Self {
+			errors: Vec::new(),
+		}
+	}
+
+	pub fn push(&mut self, error: Error) {
+		self.errors.push(error);
+	}
+
+	pub fn extend(&mut self, other: Diagnostics) {
+		self.errors.extend(other.errors);
+	}
+
+	pub fn errors(&self) -> &[Error] {
+		&self.errors
+	}
+
+	pub fn len(&self) -> usize {
+		self.errors.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.errors.is_empty()
+	}
+}
+
+impl From<Error> for Diagnostics {
+	fn from(error: Error) -> Self {
+		Self {
+			errors: vec![error],
+		}
+	}
+}
+
+impl fmt::Display for Diagnostics {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		// Only read each source file once, no matter how many errors it has
+		let mut sources: HashMap<&str, Option<String>> = HashMap::new();
+
+		for error in &self.errors {
+			let source = error.span().and_then(|span| {
+				sources.entry(span.filename())
+					.or_insert_with(|| fs::read_to_string(span.filename()).ok())
+					.clone()
+			});
+
+			writeln!(f, "{}", Diagnostic::with_source(error, source))?;
+		}
+
+		Ok(())
+	}
+}
\ No newline at end of file
@@ -1,11 +1,16 @@
+pub mod diagnostic;
+
 use std::{fmt, result};
 
-use crate::parsing::ast::Type;
+use crate::parsing::ast::{Pattern, Type};
+use crate::scanning::span::Span;
 use crate::scanning::token::{Identifier, Token};
 use crate::generating::llvm::{FunctionSignature, LLVMValue, RegisterFormat};
+use crate::generating::TYPE_FORMATS;
 
 pub type Result<T> = result::Result<T, Error>;
 
+#[derive(Debug)]
 pub enum Error {
 	FileOpenError { cause: std::io::Error },
 	FileReadError { cause: std::io::Error },
@@ -14,6 +19,9 @@ pub enum Error {
 	InvalidIdentifier { expected: Vec<Identifier>, received: Identifier },
 	TerminalTokenExpected { received_token: Option<Token>, received_identifier: Option<Identifier> },
 	UnknownToken { received: String },
+	UnterminatedString,
+	InvalidEscape { sequence: String },
+	InvalidNumber { literal: String },
 	UnknownIdentifier { received: String },
 	BinaryOperatorExpected { received: Token},
 	IdentifierExpected { received: Token },
@@ -23,15 +31,136 @@ pub enum Error {
 	StringParseError { cause: std::num::ParseIntError },
 	SymbolUndefined { name: String },
 	SymbolDeclared { name: String },
+	ReservedName { name: String },
 	StatementExpected,
 	ExpressionExpected,
 	InvalidArithmeticOperand { received: RegisterFormat },
+	MismatchedOperands { left: RegisterFormat, right: RegisterFormat },
+	IntegerOutOfRange { value: i128, expected: RegisterFormat },
+	InvalidCast { received: RegisterFormat, expected: RegisterFormat },
 	InvalidComparisonOperands { left: RegisterFormat, right: RegisterFormat },
+	MismatchedElements { expected: RegisterFormat, received: RegisterFormat },
+	InvalidIndexTarget { received: RegisterFormat },
+	TypeDeclared { name: String },
+	StructExpected { received: RegisterFormat },
+	DuplicateField { name: String },
+	UnknownField { struct_fmt: RegisterFormat, name: String },
+	MissingFields { struct_fmt: RegisterFormat, names: Vec<String> },
+	EnumExpected { received: RegisterFormat },
+	DuplicateVariant { name: String },
+	UnknownVariant { enum_fmt: RegisterFormat, name: String },
+	InvalidPayload { variant: String, expected: Vec<RegisterFormat>, received: Vec<RegisterFormat> },
+	InvalidMatchTarget { received: RegisterFormat },
+	InvalidPattern { pattern: Pattern, received: RegisterFormat },
+	UnreachablePattern { pattern: Pattern },
+	NonExhaustiveMatch { missing: Vec<String> },
+	InvalidDestructure { count: usize, received: RegisterFormat },
+	InvalidDereference { received: RegisterFormat },
+	ImmutableReference { received: RegisterFormat },
+	LocalReferenceReturned { name: String },
+	NestedOwner { container: String },
+	UseAfterMove { name: String },
+	MovedInLoop { name: String },
+	MoveOutOfReference,
+	TemporaryOwner,
+	ElementTypeUnknown,
+	UnknownMethod { received: RegisterFormat, name: String },
+	InvalidIterable { received: RegisterFormat },
+	InvalidIndex { received: RegisterFormat },
+	IndexOutOfBounds { index: i128, length: usize },
+	UnprintableValue { received: RegisterFormat },
 	InvalidAssignment { received: RegisterFormat, expected: RegisterFormat },
 	TypeUnknown { received: Type },
 	TypeExpected { received: Identifier },
-	ArgumentMismatch { expected: FunctionSignature, received: Vec<LLVMValue> },
+	ArgumentMismatch { expected: FunctionSignature, received: Vec<RegisterFormat> },
 	UnexpectedFormat { expected: RegisterFormat, received: RegisterFormat },
+	LvalueExpected,
+	MissingReturn { name: String, expected: RegisterFormat },
+	DivisionByZero,
+	ConstantExpected,
+	GlobalTypeUnsupported { received: RegisterFormat },
+	ConstantAssignment { name: String },
+	LoopControlOutsideLoop { keyword: Identifier },
+	NonPositiveStep { step: i128 },
+	Located { error: Box<Error>, span: Span },
+}
+
+impl Error {
+	// Attach a source span to the error; errors that already have a span keep the innermost one
+	pub fn with_span(self, span: &Span) -> Self {
+		match self {
+			Error::Located { .. } => self,
+			_ => Error::Located { error: Box::new(self), span: span.clone() },
+		}
+	}
+
+	// Get span of source the error occurred at, if known
+	pub fn span(&self) -> Option<&Span> {
+		match self {
+			Error::Located { span, .. } => Some(span),
+			_ => None,
+		}
+	}
+
+	// Get error without any location information attached
+	pub fn inner(&self) -> &Error {
+		match self {
+			Error::Located { error, .. } => error.inner(),
+			_ => self,
+		}
+	}
+
+	// Get a suggestion on how to fix the error, if there is one
+	pub fn help(&self) -> Option<String> {
+		match self.inner() {
+			Error::InvalidToken { expected, .. } if expected.contains(&Token::Semicolon) => Some(String::from("expected `;` after expression")),
+			Error::InvalidToken { expected, .. } if expected.len() == 1 => Some(format!("expected `{}` here", expected[0])),
+			Error::BinaryOperatorExpected { .. } => Some(String::from("expected an operator or `;` after expression")),
+			Error::UnexpectedEOF { expected } => Some(format!("add a `{expected}` before the end of the file")),
+			Error::SymbolUndefined { name } => Some(format!("`{name}` must be declared before it is used")),
+			Error::SymbolDeclared { name } => Some(format!("`{name}` is already declared in this scope")),
+			Error::ReservedName { .. } => Some(String::from("the compiled program links against C library functions and its own runtime checks; pick another name")),
+			Error::TypeUnknown { .. } => {
+				let names: Vec<&str> = TYPE_FORMATS.iter().map(|type_fmt| type_fmt.0).collect();
+				Some(format!("known types are {}, declared structs and declared enums", names.join(", ")))
+			},
+			Error::ArgumentMismatch { expected, .. } => Some(format!("function expects {} argument(s)", expected.params().len())),
+			Error::MissingReturn { .. } => Some(String::from("add a `return` statement at the end of the function")),
+			Error::LoopControlOutsideLoop { keyword } => Some(format!("`{keyword}` can only be used inside the body of a `while` or `for` loop")),
+			Error::NonPositiveStep { .. } => Some(String::from("a `for` loop counts up from the start of its range, so its step must be at least 1")),
+			Error::ConstantExpected => Some(String::from("globals can only be initialized with literals, constants and operators on them")),
+			Error::GlobalTypeUnsupported { .. } => Some(String::from("globals and constants must be of type bool or a number type")),
+			Error::MismatchedOperands { .. } => Some(String::from("use `as` to convert one side to the type of the other")),
+			Error::IntegerOutOfRange { expected, .. } => Some(format!("use a value that fits in {expected}, or a wider type")),
+			Error::InvalidCast { .. } => Some(String::from("numbers can be cast to any number type, and bools to integer types")),
+			Error::ConstantAssignment { name } => Some(format!("declare `{name}` with `let` to make it assignable")),
+			Error::InvalidIndexTarget { .. } => Some(String::from("only arrays and vecs can be indexed")),
+			Error::TypeDeclared { name } => Some(format!("`{name}` is already the name of a type")),
+			Error::MissingFields { .. } => Some(String::from("every field of a struct must be given a value")),
+			Error::InvalidMatchTarget { .. } => Some(String::from("only enums and integers can be matched")),
+			Error::InvalidPattern { .. } => Some(String::from("arms must be variants of the matched enum with a name or `_` for each value they hold, or integer literals when matching an integer")),
+			Error::UnreachablePattern { .. } => Some(String::from("remove the arm, or move it before the arm that covers it")),
+			Error::NonExhaustiveMatch { .. } => Some(String::from("add an arm for each missing pattern, or a `_` arm")),
+			Error::InvalidDestructure { .. } => Some(String::from("only tuples can be destructured, with a name or `_` for each element")),
+			Error::ImmutableReference { .. } => Some(String::from("take the reference with `&mut` to assign through it")),
+			Error::LocalReferenceReturned { .. } => Some(String::from("locals are freed when the function returns; return the value itself, or a reference the function was given")),
+			Error::NestedOwner { .. } => Some(String::from("boxes and vecs can only be held by variables, parameters and return values")),
+			Error::UseAfterMove { name } => Some(format!("pass `&{name}` to use it without moving it, or assign `{name}` a new value first")),
+			Error::MovedInLoop { name } => Some(format!("assign `{name}` a new value before the end of the loop, or declare it inside the loop")),
+			Error::MoveOutOfReference => Some(String::from("only boxes and vecs held by variables can be moved")),
+			Error::TemporaryOwner => Some(String::from("assign it to a variable first, so it can be freed once the variable goes out of scope")),
+			Error::ElementTypeUnknown => Some(String::from("give the variable a type, e.g. `let v: Vec<int> = Vec::new();`")),
+			Error::UnknownMethod { .. } => Some(String::from("vecs have the methods `push`, `pop` and `len`")),
+			Error::InvalidIterable { .. } => Some(String::from("`for` loops go over a range, e.g. `0..10`, or the elements of a vec")),
+			Error::IndexOutOfBounds { length, .. } => Some(format!("indexes start at 0, so the array's indexes are 0 through {}", length.saturating_sub(1))),
+			Error::UnprintableValue { .. } => Some(String::from("only numbers, bools and strings can be printed; print elements and fields one at a time")),
+			Error::UnterminatedString => Some(String::from("add a `\"` at the end of the string")),
+			Error::InvalidNumber { .. } => Some(String::from("an exponent must be followed by digits, e.g. 1e-3")),
+			Error::InvalidEscape { .. } => Some(String::from("known escapes are \\n, \\t, \\\", \\\\ and \\u{...}")),
+			Error::DivisionByZero => Some(String::from("the right side of `/` and `%` must not be zero")),
+			_ => None,
+		}
+	}
 }
 
 impl fmt::Display for Error {
@@ -73,6 +202,8 @@ impl fmt::Display for Error {
 				write!(f, "InvalidToken: Expected {expected_concat}; got {received}")
 			},
 			Error::UnknownToken { received } => write!(f, "UnknownToken: {received}"),
+			Error::UnterminatedString => write!(f, "UnterminatedString: String literal is missing its closing quote"),
+			Error::InvalidEscape { sequence } => write!(f, "InvalidEscape: Unknown escape sequence '{sequence}'"),
 			Error::UnknownIdentifier { received } => write!(f, "UnknownIdentifier: {received}"),
 			Error::BinaryOperatorExpected { received } => write!(f, "BinaryOperatorExpected: Expected a binary operator, but got {received}"),
 			Error::IdentifierExpected { received } => write!(f, "IdentifierExpected: Expected an identifier, but got {received}"),
@@ -80,8 +211,10 @@ impl fmt::Display for Error {
 			Error::UnexpectedEOF { expected } => write!(f, "UnexpectedEOF: Expected {expected}, but reached EOF"),
 			Error::UnexpectedLLVMValue { expected, received } => write!(f, "UnexpectedLLVMValue: Expected a {expected}, but received {received}"),
 			Error::StringParseError { cause } => write!(f, "StringParseError: {cause}"),
+			Error::InvalidNumber { literal } => write!(f, "InvalidNumber: '{literal}' is not a valid number"),
 			Error::SymbolUndefined { name } => write!(f, "SymbolUndefined: '{name}'"),
 			Error::SymbolDeclared { name } => write!(f, "SymbolDeclared: Symbol {name} has already been declared"),
+			Error::ReservedName { name } => write!(f, "ReservedName: '{name}' is used by the runtime and can't name a function or global"),
 			Error::StatementExpected => write!(f, "StatementExpected: A statement was expected"),
 			Error::ExpressionExpected => write!(f, "ExpressionExpected: An expression was expected"),
 			Error::TerminalTokenExpected { received_token, received_identifier } => {
@@ -94,7 +227,44 @@ impl fmt::Display for Error {
 				}
 			},
 			Error::InvalidArithmeticOperand { received } => write!(f, "InvalidArithmeticOperand: Attempted to perform arithmetic on {received}"),
+			Error::MismatchedOperands { left, right } => write!(f, "MismatchedOperands: Attempted to perform arithmetic on {left} and {right}"),
+			Error::IntegerOutOfRange { value, expected } => write!(f, "IntegerOutOfRange: {value} is out of the range of {expected}"),
+			Error::InvalidCast { received, expected } => write!(f, "InvalidCast: Cannot cast {received} to {expected}"),
 			Error::InvalidComparisonOperands { left, right } => write!(f, "InvalidComparisonOperands: Attempted to compare {left} and {right}"),
+			Error::MismatchedElements { expected, received } => write!(f, "MismatchedElements: Array elements must share a type; expected {expected}, but got {received}"),
+			Error::InvalidIndexTarget { received } => write!(f, "InvalidIndexTarget: Cannot index into {received}"),
+			Error::TypeDeclared { name } => write!(f, "TypeDeclared: Type {name} has already been declared"),
+			Error::StructExpected { received } => write!(f, "StructExpected: Expected a struct, but got {received}"),
+			Error::DuplicateField { name } => write!(f, "DuplicateField: Field '{name}' is given more than once"),
+			Error::UnknownField { struct_fmt, name } => write!(f, "UnknownField: {struct_fmt} has no field '{name}'"),
+			Error::MissingFields { struct_fmt, names } => write!(f, "MissingFields: {struct_fmt} is missing field(s) {}", names.join(", ")),
+			Error::EnumExpected { received } => write!(f, "EnumExpected: Expected an enum, but got {received}"),
+			Error::DuplicateVariant { name } => write!(f, "DuplicateVariant: Variant '{name}' is declared more than once"),
+			Error::UnknownVariant { enum_fmt, name } => write!(f, "UnknownVariant: {enum_fmt} has no variant '{name}'"),
+			Error::InvalidPayload { variant, expected, received } => {
+				let expected: Vec<String> = expected.iter().map(|fmt| fmt.to_string()).collect();
+				let received: Vec<String> = received.iter().map(|fmt| fmt.to_string()).collect();
+				write!(f, "InvalidPayload: {variant} holds ({}), but was given ({})", expected.join(", "), received.join(", "))
+			},
+			Error::InvalidMatchTarget { received } => write!(f, "InvalidMatchTarget: Cannot match on {received}"),
+			Error::InvalidPattern { pattern, received } => write!(f, "InvalidPattern: {pattern} can't match a value of type {received}"),
+			Error::UnreachablePattern { pattern } => write!(f, "UnreachablePattern: {pattern} is already covered by an earlier arm"),
+			Error::NonExhaustiveMatch { missing } => write!(f, "NonExhaustiveMatch: Match doesn't cover {}", missing.join(", ")),
+			Error::InvalidDestructure { count, received } => write!(f, "InvalidDestructure: Cannot destructure {received} into {count} name(s)"),
+			Error::InvalidDereference { received } => write!(f, "InvalidDereference: Cannot dereference {received}"),
+			Error::ImmutableReference { received } => write!(f, "ImmutableReference: Cannot assign through {received}"),
+			Error::LocalReferenceReturned { name } => write!(f, "LocalReferenceReturned: Cannot return a reference to local '{name}'"),
+			Error::NestedOwner { container } => write!(f, "NestedOwner: A box or vec can't be held by {container}"),
+			Error::UseAfterMove { name } => write!(f, "UseAfterMove: '{name}' is used when it may not hold a value, having been moved out of or never assigned"),
+			Error::MovedInLoop { name } => write!(f, "MovedInLoop: Value in '{name}' would be moved out again by the next iteration"),
+			Error::MoveOutOfReference => write!(f, "MoveOutOfReference: Cannot move a box or vec out of a reference"),
+			Error::TemporaryOwner => write!(f, "TemporaryOwner: A box or vec must be held by a variable to be used in place"),
+			Error::ElementTypeUnknown => write!(f, "ElementTypeUnknown: Cannot tell the element type of an empty vec"),
+			Error::UnknownMethod { received, name } => write!(f, "UnknownMethod: {received} has no method '{name}'"),
+			Error::InvalidIterable { received } => write!(f, "InvalidIterable: Cannot iterate over {received}"),
+			Error::InvalidIndex { received } => write!(f, "InvalidIndex: Expected an integer index, but got {received}"),
+			Error::IndexOutOfBounds { index, length } => write!(f, "IndexOutOfBounds: Index {index} is out of bounds for an array of length {length}"),
+			Error::UnprintableValue { received } => write!(f, "UnprintableValue: Cannot print {received}"),
 			Error::InvalidAssignment { received, expected } => write!(f, "InvalidAssigment: Attempted to assign {received} to {expected}"),
 			Error::TypeUnknown { received } => write!(f, "TypeUnknown: '{received}'"),
 			Error::TypeExpected { received } => write!(f, "TypeExpected: Expected a type, but got {received}"),
@@ -102,7 +272,7 @@ impl fmt::Display for Error {
 				write!(f, "ArgumentMismatch: Expected {expected}, but received (")?;
 
 				for (i, rec) in received.iter().enumerate() {
-					write!(f, "{fmt}", fmt=rec.format())?;
+					write!(f, "{rec}")?;
 					if i < received.len() - 1 {
 						write!(f, ",")?;
 					}
@@ -111,6 +281,15 @@ impl fmt::Display for Error {
 				write!(f, ")")
 			},
 			Error::UnexpectedFormat { received, expected} => write!(f, "UnexpectedFormat: Expected {expected}, but got {received}"),
+			Error::LvalueExpected => write!(f, "LvalueExpected: Only variables, their elements and fields, and dereferences can be assigned to or referenced"),
+			Error::MissingReturn { name, expected } => write!(f, "MissingReturn: Function '{name}' may end without returning {expected}"),
+			Error::LoopControlOutsideLoop { keyword } => write!(f, "LoopControlOutsideLoop: '{keyword}' used outside of a loop"),
+			Error::NonPositiveStep { step } => write!(f, "NonPositiveStep: Step of {step} never reaches the end of the range"),
+			Error::ConstantExpected => write!(f, "ConstantExpected: Value must be known at compile time"),
+			Error::GlobalTypeUnsupported { received } => write!(f, "GlobalTypeUnsupported: Globals can't be of type {received}"),
+			Error::ConstantAssignment { name } => write!(f, "ConstantAssignment: Cannot assign to constant '{name}'"),
+			Error::DivisionByZero => write!(f, "DivisionByZero: Divisor always evaluates to zero"),
+			Error::Located { error, span } => write!(f, "{span}: {error}"),
 		}
 	}
 }
\ No newline at end of file
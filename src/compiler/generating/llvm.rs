@@ -1,7 +1,9 @@
 use core::fmt;
+use std::collections::HashMap;
 use crate::error::{Error, Result};
+use crate::parsing::ast::Type;
 
-use super::{Identifier, Token};
+use super::{Token, TYPE_FORMATS};
 
 #[derive(Debug, Clone)]
 pub enum LLVMValue {
@@ -40,19 +42,45 @@ impl std::fmt::Display for LLVMValue {
 
 #[derive(Debug, Clone)]
 pub enum Constant {
-	Integer(i64),
+	// Value of any integer format, which must be in its range; wide enough for both i64 and u64
+	Integer { value: i128, format: RegisterFormat },
+	// Value of either float format; f32 values are kept rounded to f32 precision
+	Float { value: f64, format: RegisterFormat },
+	Boolean(bool),
+	// Pointer to the first character of a string stored in a global array of length characters
+	String { id: String, length: usize },
+	// Value of any format whose bits haven't been set, e.g. a tuple before its elements are inserted
+	Undefined { format: RegisterFormat },
+	// Box or vec holding nothing, which it's left as once moved out of or before it's assigned; an empty vec is all zeroes
+	Null { format: RegisterFormat },
 }
 
 impl Constant {
 	pub fn const_type(&self) -> String {
 		match self {
-			Constant::Integer(_) => String::from("i64"),
+			Constant::Integer { format, .. } | Constant::Float { format, .. } => format.format_type(),
+			Constant::Boolean(_) => String::from("i1"),
+			Constant::String { .. } => String::from("i8*"),
+			Constant::Undefined { format } | Constant::Null { format } => format.format_type(),
 		}
 	}
 
 	pub fn format(&self) -> RegisterFormat {
 		match self {
-			Constant::Integer(_) => RegisterFormat::Integer,
+			Constant::Integer { format, .. } | Constant::Float { format, .. } => format.clone(),
+			Constant::Boolean(_) => RegisterFormat::Boolean,
+			Constant::String { .. } => RegisterFormat::String,
+			Constant::Undefined { format } | Constant::Null { format } => format.clone(),
+		}
+	}
+
+	// Value a variable of the given format starts with when it isn't initialized
+	pub fn zero(format: &RegisterFormat) -> Option<Constant> {
+		match format {
+			_ if format.is_integer() => Some(Constant::Integer { value: 0, format: format.clone() }),
+			_ if format.is_float() => Some(Constant::Float { value: 0.0, format: format.clone() }),
+			RegisterFormat::Boolean => Some(Constant::Boolean(false)),
+			_ => None,
 		}
 	}
 }
@@ -60,7 +88,14 @@ impl Constant {
 impl fmt::Display for Constant {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
-			Constant::Integer(x) => write!(f, "{x}"),
+			Constant::Integer { value, .. } => write!(f, "{value}"),
+			// LLVM only accepts decimal floats that are exact, so write the bits of the double in hex
+			Constant::Float { value, .. } => write!(f, "0x{:016X}", value.to_bits()),
+			Constant::Boolean(x) => write!(f, "{x}"),
+			Constant::String { id, length } => write!(f, "getelementptr inbounds ([{length} x i8], [{length} x i8]* @{id}, i64 0, i64 0)"),
+			Constant::Undefined { .. } => write!(f, "undef"),
+			Constant::Null { format: RegisterFormat::Vec { .. } } => write!(f, "zeroinitializer"),
+			Constant::Null { .. } => write!(f, "null"),
 		}
 	}
 }
@@ -81,18 +116,18 @@ impl VirtualRegister {
 		}
 	}
 
-	pub fn from_identifier(id: String, identifier: Identifier, is_local: bool, symbol_table: &SymbolTable) -> Result<Self> {
+	// Refer to the storage of a variable, whose register may be named differently from the variable
+	pub fn from_symbol(symbol: &Symbol) -> Result<Self> {
+		let LLVMValue::VirtualRegister(reg) = symbol.value() else {
+			return Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: symbol.value().clone() });
+		};
+
 		Ok(Self {
-			id,
-			format: match &identifier {
-				Identifier::Symbol(s) => {
-					RegisterFormat::Identifier {
-						id_type: Box::new(symbol_table.get(&s)?.value().format())
-					}
-				},
-				_ => Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: identifier })?
+			id: reg.id().to_owned(),
+			format: RegisterFormat::Identifier {
+				id_type: Box::new(reg.format().clone())
 			},
-			is_local,
+			is_local: reg.is_local(),
 		})
 	}
 
@@ -130,9 +165,9 @@ pub struct FunctionSignature {
 }
 
 impl FunctionSignature {
-	pub fn new(params: &Vec<RegisterFormat>, return_fmt: RegisterFormat) -> Self {
+	pub fn new(params: &[RegisterFormat], return_fmt: RegisterFormat) -> Self {
 		Self {
-			params: params.clone(),
+			params: params.to_vec(),
 			return_fmt: Box::new(return_fmt),
 		}
 	}
@@ -157,15 +192,59 @@ impl fmt::Display for FunctionSignature {
 			}
 		}
 
-		write!(f, "{out}")
+		write!(f, "{out}) -> {}", self.return_fmt)
 	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RegisterFormat {
 	Void,
+	// 64-bit signed integer, named both int and i64
 	Integer,
+	Int8,
+	Int16,
+	Int32,
+	UInt8,
+	UInt16,
+	UInt32,
+	UInt64,
+	Float32,
+	// 64-bit float, named both float and f64
+	Float64,
 	Boolean,
+	String,
+	// Fixed number of values of the element format, stored next to each other
+	Array {
+		element: Box<RegisterFormat>,
+		length: usize,
+	},
+	// Declared struct, whose fields are stored in the order they were declared in
+	Struct {
+		name: String,
+		fields: Vec<(String, RegisterFormat)>,
+	},
+	// Values of any formats grouped together, passed around as a single value of a literal struct type
+	Tuple {
+		elements: Vec<RegisterFormat>,
+	},
+	// Declared enum, stored as the index of its variant followed by space for the largest payload of any variant
+	Enum {
+		name: String,
+		variants: Vec<(String, Vec<RegisterFormat>)>,
+	},
+	// Address of a value of the referent format, which is only loaded when dereferenced
+	Reference {
+		referent: Box<RegisterFormat>,
+		mutable: bool,
+	},
+	// Address of a value of the inner format on the heap, which is freed when the variable owning it goes out of scope
+	Box {
+		inner: Box<RegisterFormat>,
+	},
+	// Growable array of values of the element format on the heap, passed around as its address, length and capacity
+	Vec {
+		element: Box<RegisterFormat>,
+	},
 	Identifier {
 		id_type: Box<RegisterFormat>,
 	},
@@ -182,28 +261,208 @@ impl RegisterFormat {
 		RegisterFormat::Pointer { pointee: Box::new(self.clone()) }
 	}
 
-	pub fn can_compare_to(&self, other: &RegisterFormat, op: &Token) -> bool {
-		match (self, op, other) {
-			(RegisterFormat::Integer, _, RegisterFormat::Integer) => true,
+	// Number of bits in an integer format, or None if the format isn't an integer
+	pub fn integer_bits(&self) -> Option<u32> {
+		match self {
+			RegisterFormat::Int8 | RegisterFormat::UInt8 => Some(8),
+			RegisterFormat::Int16 | RegisterFormat::UInt16 => Some(16),
+			RegisterFormat::Int32 | RegisterFormat::UInt32 => Some(32),
+			RegisterFormat::Integer | RegisterFormat::UInt64 => Some(64),
+			_ => None,
+		}
+	}
+
+	pub fn is_integer(&self) -> bool {
+		self.integer_bits().is_some()
+	}
+
+	pub fn is_float(&self) -> bool {
+		matches!(self, RegisterFormat::Float32 | RegisterFormat::Float64)
+	}
+
+	// Whether arithmetic can be done on values of the format
+	pub fn is_number(&self) -> bool {
+		self.is_integer() || self.is_float()
+	}
+
+	pub fn is_signed(&self) -> bool {
+		matches!(self, RegisterFormat::Integer | RegisterFormat::Int8 | RegisterFormat::Int16 | RegisterFormat::Int32)
+	}
+
+	// Whether value is in the range of an integer format
+	pub fn can_hold(&self, value: i128) -> bool {
+		let Some(bits) = self.integer_bits() else {
+			return false;
+		};
+
+		if self.is_signed() {
+			-(1 << (bits - 1)) <= value && value < (1 << (bits - 1))
+		} else {
+			0 <= value && value < (1 << bits)
+		}
+	}
+
+	// Position and format of a struct's field, or None if there's no such field
+	pub fn field(&self, name: &str) -> Option<(usize, &RegisterFormat)> {
+		let RegisterFormat::Struct { fields, .. } = self else {
+			return None;
+		};
+
+		fields.iter().position(|field| field.0 == name).map(|i| (i, &fields[i].1))
+	}
+
+	// Index and payload formats of an enum's variant, or None if there's no such variant
+	pub fn variant(&self, name: &str) -> Option<(usize, &Vec<RegisterFormat>)> {
+		let RegisterFormat::Enum { variants, .. } = self else {
+			return None;
+		};
+
+		variants.iter().position(|variant| variant.0 == name).map(|i| (i, &variants[i].1))
+	}
+
+	// Struct the payload of an enum's variant is read and written through, e.g. Shape.Rect with fields named 0, 1, ...
+	pub fn payload_format(&self, index: usize) -> Option<RegisterFormat> {
+		let RegisterFormat::Enum { name, variants } = self else {
+			return None;
+		};
+		let (variant, payload) = variants.get(index)?;
+
+		Some(RegisterFormat::Struct {
+			name: format!("{name}.{variant}"),
+			fields: payload.iter().enumerate().map(|(i, fmt)| (i.to_string(), fmt.clone())).collect(),
+		})
+	}
+
+	// Number of 64-bit words an enum sets aside for the payload of its largest variant
+	pub fn payload_words(&self) -> usize {
+		let RegisterFormat::Enum { variants, .. } = self else {
+			return 0;
+		};
+
+		let largest = variants.iter().map(|variant| variant.1.iter().map(|fmt| fmt.max_size()).sum()).max().unwrap_or(0);
+		largest.div_ceil(8)
+	}
+
+	// Whether values of the format own memory on the heap, which is freed when the variable owning them goes out of scope
+	pub fn owns_memory(&self) -> bool {
+		matches!(self, RegisterFormat::Box { .. } | RegisterFormat::Vec { .. })
+	}
+
+	// Whether a box or vec is held directly inside a value of the format; they may only be held by variables,
+	// parameters and return values, so freeing a value never has to look inside it
+	pub fn holds_owner(&self) -> bool {
+		match self {
+			RegisterFormat::Array { element, .. } | RegisterFormat::Vec { element } => element.owns_memory(),
+			RegisterFormat::Struct { fields, .. } => fields.iter().any(|field| field.1.owns_memory()),
+			RegisterFormat::Tuple { elements } => elements.iter().any(RegisterFormat::owns_memory),
+			RegisterFormat::Enum { variants, .. } => variants.iter().any(|variant| variant.1.iter().any(RegisterFormat::owns_memory)),
+			RegisterFormat::Box { inner } => inner.owns_memory(),
 			_ => false,
 		}
 	}
 
+	// Signature of a built-in method and whether it changes the value it's called on, or None if there's no such method
+	pub fn method(&self, name: &str) -> Option<(FunctionSignature, bool)> {
+		let RegisterFormat::Vec { element } = self else {
+			return None;
+		};
+
+		match name {
+			"push" => Some((FunctionSignature::new(&[*element.clone()], RegisterFormat::Void), true)),
+			"pop" => Some((FunctionSignature::new(&[], *element.clone()), true)),
+			"len" => Some((FunctionSignature::new(&[], RegisterFormat::Integer), false)),
+			_ => None,
+		}
+	}
+
+	// Bytes a value of the format takes up in memory at most; every scalar is counted as 8 bytes,
+	// which is never less than its size and alignment together
+	pub fn max_size(&self) -> usize {
+		match self {
+			RegisterFormat::Array { element, length } => element.max_size() * length,
+			RegisterFormat::Struct { fields, .. } => fields.iter().map(|field| field.1.max_size()).sum(),
+			RegisterFormat::Tuple { elements } => elements.iter().map(|element| element.max_size()).sum(),
+			RegisterFormat::Enum { .. } => 8 * (1 + self.payload_words()),
+			RegisterFormat::Vec { .. } => 24,
+			_ => 8,
+		}
+	}
+
+	// Format a value has once loaded, if self refers to its storage
+	pub fn rvalue_format(&self) -> RegisterFormat {
+		match self {
+			RegisterFormat::Identifier { id_type } => *id_type.clone(),
+			RegisterFormat::Pointer { pointee } => *pointee.clone(),
+			_ => self.clone(),
+		}
+	}
+
+	// Numbers can only be compared to numbers of the same format
+	pub fn can_compare_to(&self, other: &RegisterFormat, _op: &Token) -> bool {
+		self.is_number() && self == other
+	}
+
+	// Format a number literal of this format, int or f64, takes where a value of expected is expected, if given;
+	// rather than being an int or float, it takes on expected if that's a number format of the same kind
+	pub fn literal_format(&self, expected: Option<&RegisterFormat>) -> RegisterFormat {
+		match expected {
+			Some(fmt) if (self.is_integer() && fmt.is_integer()) || (self.is_float() && fmt.is_float()) => fmt.clone(),
+			_ => self.clone(),
+		}
+	}
+
+	// A mutable reference can be used wherever an immutable reference to the same format is expected
 	pub fn can_convert_to(&self, other: &RegisterFormat) -> bool {
+		if let (RegisterFormat::Reference { referent, mutable }, RegisterFormat::Reference { referent: other_referent, mutable: other_mutable }) = (self, other) {
+			return referent == other_referent && (*mutable || !*other_mutable);
+		}
+
+		matches!((self, other), (RegisterFormat::Boolean, RegisterFormat::Boolean) | (RegisterFormat::String, RegisterFormat::String))
+			|| ((self.is_number() || matches!(self, RegisterFormat::Array { .. } | RegisterFormat::Struct { .. } | RegisterFormat::Tuple { .. } | RegisterFormat::Enum { .. } | RegisterFormat::Box { .. } | RegisterFormat::Vec { .. })) && self == other)
+	}
+
+	// Instruction converting a value of this format to other with 'as', or None if the cast isn't allowed
+	// Integers are extended by the sign of their own format, and floats are rounded towards zero when made integers
+	pub fn cast_instruction(&self, other: &RegisterFormat) -> Option<&'static str> {
 		match (self, other) {
-			(RegisterFormat::Integer, RegisterFormat::Integer) => true,
-			(RegisterFormat::Boolean, RegisterFormat::Boolean) => true,
-			_ => false,
+			(RegisterFormat::Boolean, to) if to.is_integer() => Some("zext"),
+			(from, to) if from.is_integer() && to.is_integer() => match (from.integer_bits()?, to.integer_bits()?) {
+				(from_bits, to_bits) if from_bits < to_bits && from.is_signed() => Some("sext"),
+				(from_bits, to_bits) if from_bits < to_bits => Some("zext"),
+				(from_bits, to_bits) if from_bits > to_bits => Some("trunc"),
+				_ => Some("bitcast"),
+			},
+			(from, to) if from.is_integer() && to.is_float() => Some(if from.is_signed() { "sitofp" } else { "uitofp" }),
+			(from, to) if from.is_float() && to.is_integer() => Some(if to.is_signed() { "fptosi" } else { "fptoui" }),
+			(RegisterFormat::Float32, RegisterFormat::Float64) => Some("fpext"),
+			(RegisterFormat::Float64, RegisterFormat::Float32) => Some("fptrunc"),
+			_ => None,
 		}
 	}
 
 	pub fn format_type(&self) -> String {
 		match self {
 			RegisterFormat::Void => String::from("void"),
-			RegisterFormat::Identifier { id_type } => String::from(format!("{}*", id_type.format_type())),
-			RegisterFormat::Integer => String::from("i64"),
+			RegisterFormat::Identifier { id_type } => format!("{}*", id_type.format_type()),
+			RegisterFormat::Int8 | RegisterFormat::UInt8 => String::from("i8"),
+			RegisterFormat::Int16 | RegisterFormat::UInt16 => String::from("i16"),
+			RegisterFormat::Int32 | RegisterFormat::UInt32 => String::from("i32"),
+			RegisterFormat::Integer | RegisterFormat::UInt64 => String::from("i64"),
+			RegisterFormat::Float32 => String::from("float"),
+			RegisterFormat::Float64 => String::from("double"),
 			RegisterFormat::Boolean => String::from("i1"),
-			RegisterFormat::Pointer { pointee } => String::from(format!("{}*", pointee.format_type())),
+			RegisterFormat::String => String::from("i8*"),
+			RegisterFormat::Array { element, length } => format!("[{length} x {}]", element.format_type()),
+			RegisterFormat::Struct { name, .. } => format!("%struct.{name}"),
+			RegisterFormat::Tuple { elements } => {
+				let elements: Vec<String> = elements.iter().map(|element| element.format_type()).collect();
+				format!("{{ {} }}", elements.join(", "))
+			},
+			RegisterFormat::Enum { name, .. } => format!("%enum.{name}"),
+			RegisterFormat::Reference { referent, .. } => format!("{}*", referent.format_type()),
+			RegisterFormat::Box { inner } => format!("{}*", inner.format_type()),
+			RegisterFormat::Vec { element } => format!("{{ {}*, i64, i64 }}", element.format_type()),
+			RegisterFormat::Pointer { pointee } => format!("{}*", pointee.format_type()),
 			RegisterFormat::Function { .. } => String::from("function"),
 		}
 	}
@@ -215,6 +474,15 @@ impl RegisterFormat {
 			Err(Error::UnexpectedFormat { received: other.to_owned(), expected: self.to_owned() })
 		}
 	}
+
+	// Like expect, but also allowing a value of other to be used as this format, e.g. a mutable reference as an immutable one
+	pub fn expect_convertible(&self, other: RegisterFormat) -> Result<()> {
+		if other.can_convert_to(self) {
+			Ok(())
+		} else {
+			self.expect(other)
+		}
+	}
 }
 
 impl fmt::Display for RegisterFormat {
@@ -223,6 +491,26 @@ impl fmt::Display for RegisterFormat {
 			RegisterFormat::Void => write!(f, "void"),
 			RegisterFormat::Boolean => write!(f, "bool"),
 			RegisterFormat::Integer => write!(f, "int"),
+			RegisterFormat::Int8 => write!(f, "i8"),
+			RegisterFormat::Int16 => write!(f, "i16"),
+			RegisterFormat::Int32 => write!(f, "i32"),
+			RegisterFormat::UInt8 => write!(f, "u8"),
+			RegisterFormat::UInt16 => write!(f, "u16"),
+			RegisterFormat::UInt32 => write!(f, "u32"),
+			RegisterFormat::UInt64 => write!(f, "u64"),
+			RegisterFormat::Float32 => write!(f, "f32"),
+			RegisterFormat::Float64 => write!(f, "float"),
+			RegisterFormat::String => write!(f, "str"),
+			RegisterFormat::Array { element, length } => write!(f, "[{element}; {length}]"),
+			RegisterFormat::Struct { name, .. } | RegisterFormat::Enum { name, .. } => write!(f, "{name}"),
+			RegisterFormat::Tuple { elements } => {
+				let elements: Vec<String> = elements.iter().map(|element| element.to_string()).collect();
+				write!(f, "({})", elements.join(", "))
+			},
+			RegisterFormat::Reference { referent, mutable: true } => write!(f, "&mut {referent}"),
+			RegisterFormat::Reference { referent, mutable: false } => write!(f, "&{referent}"),
+			RegisterFormat::Box { inner } => write!(f, "Box<{inner}>"),
+			RegisterFormat::Vec { element } => write!(f, "Vec<{element}>"),
 			RegisterFormat::Pointer { pointee } => write!(f, "{pointee}"),
 			RegisterFormat::Identifier { id_type } => write!(f, "{id_type}"),
 			RegisterFormat::Function { .. } => write!(f, "function"),
@@ -236,6 +524,14 @@ pub enum Symbol {
 		name: String,
 		value: LLVMValue,
 	},
+	Global {
+		name: String,
+		value: LLVMValue,
+	},
+	Constant {
+		name: String,
+		value: LLVMValue,
+	},
 	Function {
 		name: String,
 		value: LLVMValue,
@@ -246,6 +542,8 @@ impl Symbol {
 	pub fn name(&self) -> &str {
 		match self {
 			Symbol::Local { name, .. } => name.as_str(),
+			Symbol::Global { name, .. } => name.as_str(),
+			Symbol::Constant { name, .. } => name.as_str(),
 			Symbol::Function { name, .. } => name.as_str(),
 		}
 	}
@@ -253,6 +551,8 @@ impl Symbol {
 	pub fn value(&self) -> &LLVMValue {
 		match self {
 			Symbol::Local { value, .. } => value,
+			Symbol::Global { value, .. } => value,
+			Symbol::Constant { value, .. } => value,
 			Symbol::Function { value, .. } => value,
 		}
 	}
@@ -300,6 +600,10 @@ impl SymbolTable {
 		self.buckets.len()
 	}
 
+	pub fn is_empty(&self) -> bool {
+		self.buckets.is_empty()
+	}
+
 	pub fn insert(&mut self, symbol: Symbol) {
 		let hash = self.hash(symbol.name());
 
@@ -349,14 +653,14 @@ impl SymbolTable {
 				let next = curr.as_mut().unwrap().next.take();
 				*curr = next;
 
-				return ();
+				return;
 			} else if curr.as_ref().unwrap().next().is_none() {
-				return ();
+				return;
 			} else if curr.as_ref().unwrap().next().as_ref().unwrap().symbol().name().eq(name) {
 				let next = curr.as_mut().unwrap().next.as_mut().unwrap().next.take();
 				*curr = next;
 
-				return ();
+				return;
 			} else {
 				curr = &mut curr.as_mut().unwrap().next;
 			}
@@ -378,26 +682,49 @@ impl SymbolTable {
 		let mut pow: u64 = 1;
 
 		let mut hash: u64 = 0;
-		for (_, c) in name.chars().enumerate() {
+		for c in name.chars() {
 			hash = (hash + (c as u64 % len) * pow) % len;
 			pow = (pow * prime) % len;
 		}
 
-		return hash as usize;
+		hash as usize
 	}
 
-	pub fn create_local(&self, name: &String, format: &RegisterFormat) -> (Symbol, VirtualRegister) {
-		let reg = VirtualRegister::new(name.to_owned(), format.clone(), true);
+	pub fn create_local(&self, name: &str, format: &RegisterFormat) -> (Symbol, VirtualRegister) {
+		self.create_local_with_id(name, name, format)
+	}
+
+	// Create a local whose register is named id rather than the local's own name
+	pub fn create_local_with_id(&self, name: &str, id: &str, format: &RegisterFormat) -> (Symbol, VirtualRegister) {
+		let reg = VirtualRegister::new(id.to_owned(), format.clone(), true);
 		let symbol = Symbol::Local {
 			name: name.to_owned(),
 			value: LLVMValue::VirtualRegister(reg.clone()),
 		};
-		let pointer = VirtualRegister::new(name.to_owned(), format.to_pointer(), true);
+		let pointer = VirtualRegister::new(id.to_owned(), format.to_pointer(), true);
+
+		(symbol, pointer)
+	}
+
+	pub fn create_global(&self, name: &str, format: &RegisterFormat) -> (Symbol, VirtualRegister) {
+		let reg = VirtualRegister::new(name.to_owned(), format.clone(), false);
+		let symbol = Symbol::Global {
+			name: name.to_owned(),
+			value: LLVMValue::VirtualRegister(reg),
+		};
+		let pointer = VirtualRegister::new(name.to_owned(), format.to_pointer(), false);
 
 		(symbol, pointer)
 	}
 
-	pub fn create_function(&self, name: &String, signature: &FunctionSignature) -> (Symbol, VirtualRegister) {
+	pub fn create_constant(&self, name: &str, value: &Constant) -> Symbol {
+		Symbol::Constant {
+			name: name.to_owned(),
+			value: LLVMValue::Constant(value.clone()),
+		}
+	}
+
+	pub fn create_function(&self, name: &str, signature: &FunctionSignature) -> (Symbol, VirtualRegister) {
 		let reg = VirtualRegister::new(name.to_owned(), RegisterFormat::Function { signature: signature.to_owned() }, false);
 		let symbol = Symbol::Function {
 			name: name.to_owned(),
@@ -408,6 +735,146 @@ impl SymbolTable {
 	}
 }
 
+// Formats of the types that can be named in a program, i.e. the built-in types and declared structs and enums
+#[derive(Debug)]
+pub struct TypeRegistry {
+	types: Vec<(String, RegisterFormat)>,
+}
+
+impl Default for TypeRegistry {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl TypeRegistry {
+	pub fn new() -> Self {
+		Self {
+			types: TYPE_FORMATS.iter().map(|(name, fmt)| (name.to_string(), fmt.clone())).collect(),
+		}
+	}
+
+	pub fn get(&self, name: &str) -> Option<&RegisterFormat> {
+		self.types.iter().find_map(|(type_name, fmt)| if type_name == name { Some(fmt) } else { None })
+	}
+
+	// Add a named type; a name can't be declared twice, nor be the name of a built-in type
+	pub fn declare(&mut self, name: &str, format: RegisterFormat) -> Result<()> {
+		if self.get(name).is_some() {
+			return Err(Error::TypeDeclared { name: name.to_owned() });
+		}
+
+		self.types.push((name.to_owned(), format));
+		Ok(())
+	}
+
+	// Look up the register format a type refers to
+	pub fn format_from_type(&self, source: &Type) -> Result<RegisterFormat> {
+		let fmt = match source {
+			Type::Named { type_name } => self.get(type_name).cloned().ok_or_else(|| Error::TypeUnknown { received: source.to_owned() }),
+			Type::Array { element, length } => Ok(RegisterFormat::Array { element: Box::new(self.format_from_type(element)?), length: *length }),
+			Type::Tuple { elements } => Ok(RegisterFormat::Tuple { elements: elements.iter().map(|element| self.format_from_type(element)).collect::<Result<Vec<RegisterFormat>>>()? }),
+			Type::Reference { referent, mutable } => Ok(RegisterFormat::Reference { referent: Box::new(self.format_from_type(referent)?), mutable: *mutable }),
+			Type::Box { inner } => Ok(RegisterFormat::Box { inner: Box::new(self.format_from_type(inner)?) }),
+			Type::Vec { element } => Ok(RegisterFormat::Vec { element: Box::new(self.format_from_type(element)?) }),
+			Type::Void => Ok(RegisterFormat::Void),
+		}?;
+
+		if fmt.holds_owner() {
+			return Err(Error::NestedOwner { container: fmt.to_string() });
+		}
+
+		Ok(fmt)
+	}
+}
+
+// Symbol table for locals, split into the nested scopes of a function's blocks
+// Newer symbols are found first, so inner declarations shadow outer ones until their scope ends
+#[derive(Debug)]
+pub struct ScopedSymbolTable {
+	table: SymbolTable,
+	// Names declared in each open scope, innermost last
+	scopes: Vec<Vec<String>>,
+	// Number of locals created with each name, so shadowing locals get registers of their own
+	declared_counts: HashMap<String, u32>,
+}
+
+impl ScopedSymbolTable {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			table: SymbolTable::new(capacity),
+			scopes: vec![Vec::new()],
+			declared_counts: HashMap::new(),
+		}
+	}
+
+	pub fn push_scope(&mut self) {
+		self.scopes.push(Vec::new());
+	}
+
+	// Remove every symbol declared in the innermost scope
+	pub fn pop_scope(&mut self) {
+		if let Some(names) = self.scopes.pop() {
+			for name in names.iter().rev() {
+				self.table.remove(name);
+			}
+		}
+
+		if self.scopes.is_empty() {
+			self.scopes.push(Vec::new());
+		}
+	}
+
+	// Whether name was declared in the innermost scope, as opposed to an enclosing one
+	pub fn is_declared_in_scope(&self, name: &str) -> bool {
+		self.scopes.last().is_some_and(|names| names.iter().any(|declared| declared == name))
+	}
+
+	pub fn get(&self, name: &str) -> Result<&Symbol> {
+		self.table.get(name)
+	}
+
+	// Add symbol to the innermost scope
+	pub fn insert(&mut self, symbol: Symbol) {
+		if let Some(names) = self.scopes.last_mut() {
+			names.push(symbol.name().to_owned());
+		}
+
+		self.table.insert(symbol);
+	}
+
+	// Create a local with a register name not used by any other local since the table was cleared
+	// Identifiers can't hold dots, so the '..' of a shadowing local's name can't clash with a parameter's '.arg'
+	// or a generated name like a label's
+	pub fn create_local(&mut self, name: &str, format: &RegisterFormat) -> (Symbol, VirtualRegister) {
+		let count = self.declared_counts.entry(name.to_owned()).or_insert(0);
+		let id = if *count == 0 { name.to_owned() } else { format!("{name}..{count}") };
+		*count += 1;
+
+		self.table.create_local_with_id(name, &id, format)
+	}
+
+	pub fn clear(&mut self) {
+		self.table.clear();
+		self.scopes = vec![Vec::new()];
+		self.declared_counts.clear();
+	}
+}
+
+// Look up a name as seen from inside a function, where locals shadow globals
+pub fn lookup_symbol<'a>(locals: &'a ScopedSymbolTable, globals: &'a SymbolTable, name: &str) -> Result<&'a Symbol> {
+	locals.get(name).or_else(|_| globals.get(name))
+}
+
+// Value of the constant a name refers to, if it refers to one
+pub fn named_constant(locals: &ScopedSymbolTable, globals: &SymbolTable, name: &str) -> Option<Constant> {
+	match lookup_symbol(locals, globals, name) {
+		Ok(Symbol::Constant { value: LLVMValue::Constant(value), .. }) => Some(value.clone()),
+		_ => None,
+	}
+}
+
+#[derive(Debug, Clone)]
 pub struct Label {
 	id: String,
 }
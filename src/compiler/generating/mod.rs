@@ -1,27 +1,55 @@
 pub mod writer;
 pub mod llvm;
 
+use crate::analyzing::constant;
 use crate::error::*;
 
-use crate::parsing::ast::{ASTNode, FunctionParameter, Type};
-use crate::parsing::Parser;
+use crate::parsing::ast::{ASTNode, EnumVariant, FunctionParameter, MatchArm, NodeKind, Pattern, Program, StructField, Type};
 use crate::scanning::token::*;
 use llvm::*;
 use writer::Writer;
 
+// Built-in types, which every type registry starts with
 pub const TYPE_FORMATS: &[(&str, RegisterFormat)] = &[
 	("bool", RegisterFormat::Boolean),
 	("int", RegisterFormat::Integer),
+	("i8", RegisterFormat::Int8),
+	("i16", RegisterFormat::Int16),
+	("i32", RegisterFormat::Int32),
+	("i64", RegisterFormat::Integer),
+	("u8", RegisterFormat::UInt8),
+	("u16", RegisterFormat::UInt16),
+	("u32", RegisterFormat::UInt32),
+	("u64", RegisterFormat::UInt64),
+	("float", RegisterFormat::Float64),
+	("f32", RegisterFormat::Float32),
+	("f64", RegisterFormat::Float64),
+	("str", RegisterFormat::String),
 ];
 
 #[derive(Debug)]
 pub struct Generator {
 	writer: Writer,
 	next_register: u32,
+	// Number of stack values allocated in the function being generated, which are named rather than numbered since their allocas are moved to its entry block
+	stack_values: u32,
 	free_register_count: u32,
 	label_count: u32,
-	local_symbol_table: SymbolTable,
+	local_symbol_table: ScopedSymbolTable,
 	global_symbol_table: SymbolTable,
+	types: TypeRegistry,
+	// Continue and break targets of the loops being generated, with the number of scopes open outside each, innermost last
+	loop_labels: Vec<(Label, Label, usize)>,
+	// Storage of the locals holding boxes and vecs in each open scope, innermost last, which are freed when their scope ends
+	owners: Vec<Vec<LLVMValue>>,
+	// Text of every string literal, emitted as globals once the functions are written
+	strings: Vec<String>,
+	// Element formats of the vecs whose methods are called, whose helper functions are emitted once the functions are written
+	vec_elements: Vec<RegisterFormat>,
+	// Whether any box is allocated, which makes the allocation trap be emitted
+	allocates_boxes: bool,
+	checked_arithmetic: bool,
+	checked_indexing: bool,
 }
 
 impl Generator {
@@ -29,19 +57,38 @@ impl Generator {
 		Self {
 			writer,
 			next_register: 1,
+			stack_values: 0,
 			free_register_count: 0,
 			label_count: 0,
-			local_symbol_table: SymbolTable::new(64),
+			local_symbol_table: ScopedSymbolTable::new(64),
 			global_symbol_table: SymbolTable::new(64),
+			types: TypeRegistry::new(),
+			loop_labels: Vec::new(),
+			owners: vec![Vec::new()],
+			strings: Vec::new(),
+			vec_elements: Vec::new(),
+			allocates_boxes: false,
+			checked_arithmetic: false,
+			checked_indexing: false,
 		}
 	}
 
 	pub fn from_filename(filename: String) -> Result<Self> {
 		Writer::from_filename(filename)
-			.map(|writer| Self::new(writer))
+			.map(Self::new)
 			.map_err(|cause| Error::FileOpenError { cause })
 	}
 
+	// Guard divisions with runtime checks that abort instead of dividing by zero or overflowing
+	pub fn set_checked_arithmetic(&mut self, checked_arithmetic: bool) {
+		self.checked_arithmetic = checked_arithmetic;
+	}
+
+	// Guard indexes that aren't known at compile time with runtime checks that abort instead of indexing out of bounds
+	pub fn set_checked_indexing(&mut self, checked_indexing: bool) {
+		self.checked_indexing = checked_indexing;
+	}
+
 	pub fn writer(&self) -> &Writer {
 		&self.writer
 	}
@@ -50,7 +97,7 @@ impl Generator {
 		self.next_register
 	}
 
-	pub fn local_symbol_table(&self) -> &SymbolTable {
+	pub fn local_symbol_table(&self) -> &ScopedSymbolTable {
 		&self.local_symbol_table
 	}
 
@@ -58,16 +105,82 @@ impl Generator {
 		&self.global_symbol_table
 	}
 
-	pub fn generate(&mut self, parser: &mut Parser) -> Result<()> {
+	// Lower an analyzed program to LLVM
+	pub fn generate(&mut self, program: &Program) -> Result<()> {
 		self.writer.write_preamble()?;
 
+		// Structs and enums are declared first, since any signature or declaration may use them
+		let mut has_types = false;
+		for statement in &program.statements {
+			match &statement.kind {
+				NodeKind::StructDefinition { name, fields } => self.declare_struct(name, fields).map_err(|error| error.with_span(&statement.span))?,
+				NodeKind::EnumDefinition { name, variants } => self.declare_enum(name, variants).map_err(|error| error.with_span(&statement.span))?,
+				_ => continue,
+			}
+
+			has_types = true;
+		}
+
+		if has_types {
+			self.writer.writeln("")?;
+		}
+
+		// Declare every function and global up front so function bodies don't depend on definition order
+		let mut has_globals = false;
+		for statement in &program.statements {
+			match &statement.kind {
+				NodeKind::FunctionDefinition { name, parameters, return_type, .. } => {
+					self.declare_function(name, parameters, return_type).map_err(|error| error.with_span(&statement.span))?;
+				},
+				NodeKind::Let { name, val_type, value } => {
+					self.generate_global(name, val_type.as_ref(), value.as_deref(), false).map_err(|error| error.with_span(&statement.span))?;
+					has_globals = true;
+				},
+				NodeKind::Const { .. } => {
+					self.ast_to_llvm(statement, None)?;
+				},
+				_ => {},
+			}
+		}
+
+		if has_globals {
+			self.writer.writeln("")?;
+		}
+
 		// Allocate variable stack space and write to output
-		while let Some(function) = parser.parse_global_statement()? {
-			self.free_register_count = self.next_register - 1;
+		for function in &program.statements {
+			if let NodeKind::FunctionDefinition { .. } = &function.kind {
+				self.free_register_count = self.next_register - 1;
 
-			self.ast_to_llvm(&function, None)?;
+				self.ast_to_llvm(function, None)?;
+			}
 		}
 
+		for (i, text) in self.strings.iter().enumerate() {
+			self.writer.write_string_constant(&format!("str.{i}"), text)?;
+		}
+		if !self.strings.is_empty() {
+			self.writer.writeln("")?;
+		}
+
+		if self.checked_arithmetic {
+			self.writer.write_arithmetic_traps()?;
+		}
+		if self.checked_indexing {
+			self.writer.write_index_trap()?;
+		}
+		for element in &self.vec_elements {
+			self.writer.write_vec_helpers(element)?;
+		}
+		if !self.vec_elements.is_empty() {
+			self.writer.write_pop_trap()?;
+		}
+		if self.allocates_boxes || !self.vec_elements.is_empty() {
+			self.writer.write_alloc_trap()?;
+		}
+		if self.checked_arithmetic || self.checked_indexing || self.allocates_boxes || !self.vec_elements.is_empty() {
+			self.writer.write_trap_declarations()?;
+		}
 		self.writer.write_postamble()?;
 
 		Ok(())
@@ -96,41 +209,155 @@ impl Generator {
 
 	// Traverse AST and generate LLVM for the tree
 	pub fn ast_to_llvm(&mut self, root: &ASTNode, expected_fmt: Option<RegisterFormat>) -> Result<LLVMValue> {
-		match root {
-			ASTNode::Literal(x) => Ok(self.generate_literal(x)?),
-			ASTNode::Binary {token, left, right} => Ok(self.generate_binary(token, *(*left).clone(), *(*right).clone())?),
-			ASTNode::Let { name, val_type, value } => Ok(self.generate_let(name, val_type, value)?),
-			ASTNode::If { expr, block, else_block } => Ok(self.generate_if(expr, block, else_block, &expected_fmt)?),
-			ASTNode::While { expr, block } => Ok(self.generate_while(expr, block, &expected_fmt)?),
-			ASTNode::FunctionDefinition { name, parameters, body_block, return_type } => Ok(self.generate_function(name.to_owned(), parameters, body_block, return_type)?),
-			ASTNode::Return { return_val } => Ok(self.generate_return(return_val, &expected_fmt)?),
-			ASTNode::FunctionCall { name, args } => Ok(self.generate_function_call(name, args)?),
-			ASTNode::Print { expr } => Ok(self.generate_print(expr)?),
-		}
+		match &root.kind {
+			NodeKind::Literal(x) => self.generate_literal(x),
+			NodeKind::Binary {token, left, right} => self.generate_binary(token, left, right),
+			NodeKind::Unary { token, expr } => self.generate_unary(token, expr),
+			NodeKind::Cast { expr, cast_type } => self.generate_cast(expr, cast_type),
+			NodeKind::Reference { expr, mutable } => self.generate_reference(expr, *mutable),
+			NodeKind::Deref { expr } => self.generate_deref(expr),
+			NodeKind::BoxNew { value } => self.generate_box_new(value, None),
+			NodeKind::VecNew => Err(Error::ElementTypeUnknown),
+			NodeKind::ArrayLiteral { elements } => self.generate_array_literal(elements, None),
+			NodeKind::TupleLiteral { elements } => self.generate_tuple_literal(elements, None),
+			NodeKind::Index { array, index } => self.generate_index(array, index),
+			NodeKind::StructLiteral { name, fields } => self.generate_struct_literal(name, fields),
+			NodeKind::FieldAccess { expr, field } => self.generate_field_access(expr, field),
+			NodeKind::MethodCall { expr, method, args } => self.generate_method_call(expr, method, args),
+			NodeKind::VariantLiteral { enum_name, variant, values } => self.generate_variant_literal(enum_name, variant, values),
+			// Structs and enums were declared before anything else was generated
+			NodeKind::StructDefinition { .. } | NodeKind::EnumDefinition { .. } => Ok(LLVMValue::None),
+			NodeKind::Let { name, val_type, value } => self.generate_let(name, val_type, value),
+			NodeKind::LetTuple { names, val_type, value } => self.generate_let_tuple(names, val_type, value),
+			NodeKind::Const { name, const_type, value } => self.generate_global(name, Some(const_type), Some(value), true),
+			NodeKind::If { expr, block, else_block } => self.generate_if(expr, block, else_block, &expected_fmt),
+			NodeKind::While { expr, block } => self.generate_while(expr, block, &expected_fmt),
+			NodeKind::For { name, start, end, inclusive, step, block } => self.generate_for(name, start, end, *inclusive, step, block, &expected_fmt),
+			NodeKind::ForEach { name, vec, block } => self.generate_for_each(name, vec, block, &expected_fmt),
+			NodeKind::Match { expr, arms } => self.generate_match(expr, arms, &expected_fmt),
+			NodeKind::Break => self.generate_loop_control(Identifier::Break),
+			NodeKind::Continue => self.generate_loop_control(Identifier::Continue),
+			NodeKind::FunctionDefinition { name, parameters, body_block, return_type } => self.generate_function(name, parameters, body_block, return_type),
+			NodeKind::Return { return_val } => self.generate_return(return_val, &expected_fmt),
+			NodeKind::FunctionCall { name, args } => self.generate_function_call(name, args),
+			NodeKind::Print { exprs } => self.generate_print(exprs),
+		}.map_err(|error| error.with_span(&root.span))
+	}
 
+	// Get a pointer to the first character of a global holding the given text
+	// Identical strings share one global
+	pub fn intern_string(&mut self, text: &str) -> Constant {
+		let index = self.strings.iter()
+			.position(|s| s == text)
+			.unwrap_or_else(|| {
+				self.strings.push(text.to_owned());
+				self.strings.len() - 1
+			});
+
+		Constant::String { id: format!("str.{index}"), length: text.len() + 1 }
 	}
 
 	// Generate literal value based on given type
 	pub fn generate_literal(&mut self, literal: &Literal) -> Result<LLVMValue> {
 		match literal {
-			Literal::Integer(x) => Ok(LLVMValue::Constant(Constant::Integer(*x))),
+			Literal::Integer(x) => Ok(LLVMValue::Constant(Constant::Integer { value: *x as i128, format: RegisterFormat::Integer })),
+			Literal::Float(x) => Ok(LLVMValue::Constant(Constant::Float { value: *x, format: RegisterFormat::Float64 })),
+			Literal::String(s) => Ok(LLVMValue::Constant(self.intern_string(s))),
 			Literal::Identifier(i) => match i {
-				Identifier::Symbol(x) => Ok(LLVMValue::VirtualRegister(VirtualRegister::from_identifier(x.to_owned(), i.to_owned(), true, self.local_symbol_table())?)),
+				Identifier::Symbol(name) => match lookup_symbol(&self.local_symbol_table, &self.global_symbol_table, name)? {
+					// Constants are used directly rather than loaded from memory
+					Symbol::Constant { value, .. } => Ok(value.clone()),
+					symbol => Ok(LLVMValue::VirtualRegister(VirtualRegister::from_symbol(symbol)?)),
+				},
 				_ => Err(Error::TerminalTokenExpected { received_token: None, received_identifier: Some(i.clone()) })
 			},
 		}
 	}
 
+	// Generate a node's value as an operand, used where a value of fmt is expected if given
+	// Number literals are computed here in the format analysis gave them, including ones in aggregate literals
+	pub fn generate_value(&mut self, node: &ASTNode, fmt: Option<&RegisterFormat>) -> Result<LLVMValue> {
+		if let (NodeKind::ArrayLiteral { elements }, Some(RegisterFormat::Array { element, .. })) = (&node.kind, fmt) {
+			let mut val = self.generate_array_literal(elements, Some(element)).map_err(|error| error.with_span(&node.span))?;
+			self.ensure_rvalue(&mut val)?;
+
+			return Ok(val);
+		}
+
+		if let (NodeKind::TupleLiteral { elements }, Some(RegisterFormat::Tuple { elements: element_fmts })) = (&node.kind, fmt) {
+			return self.generate_tuple_literal(elements, Some(element_fmts)).map_err(|error| error.with_span(&node.span));
+		}
+
+		if let (NodeKind::BoxNew { value }, Some(RegisterFormat::Box { inner })) = (&node.kind, fmt) {
+			return self.generate_box_new(value, Some(inner)).map_err(|error| error.with_span(&node.span));
+		}
+
+		// An empty vec has no elements yet, nor space for any
+		if let (NodeKind::VecNew, Some(fmt @ RegisterFormat::Vec { .. })) = (&node.kind, fmt) {
+			return Ok(LLVMValue::Constant(Constant::Null { format: fmt.clone() }));
+		}
+
+		if constant::is_number_literal(node) {
+			if let Some(value) = constant::evaluate_literal(node, &constant::own_format(node).literal_format(fmt)) {
+				return Ok(LLVMValue::Constant(value));
+			}
+		}
+
+		let mut val = self.ast_to_llvm(node, None)?;
+		let storage = val.clone();
+		self.ensure_rvalue(&mut val)?;
+
+		// Moving a box or vec out of a variable leaves the variable null, so freeing it when its scope ends does nothing
+		if let (LLVMValue::VirtualRegister(reg), true) = (&storage, val.format().owns_memory()) {
+			if let RegisterFormat::Identifier { .. } = reg.format() {
+				self.writer.write_store(&LLVMValue::Constant(Constant::Null { format: val.format() }), &storage)?;
+			}
+		}
+
+		Ok(val)
+	}
+
+	// Generate operands that must share a format; integer literals take the format of the first other operand
+	pub fn generate_operands(&mut self, nodes: &[&ASTNode]) -> Result<Vec<LLVMValue>> {
+		let mut vals: Vec<Option<LLVMValue>> = Vec::new();
+		for node in nodes {
+			vals.push(if constant::is_number_literal(node) { None } else { Some(self.generate_value(node, None)?) });
+		}
+
+		let shared = vals.iter().flatten().next().map_or(RegisterFormat::Integer, |val| val.format());
+		let mut operands: Vec<LLVMValue> = Vec::new();
+		for (node, val) in nodes.iter().zip(vals) {
+			operands.push(match val {
+				Some(val) => val,
+				None => self.generate_value(node, Some(&shared))?,
+			});
+		}
+
+		Ok(operands)
+	}
+
 	// Generate binary statement given operation and left/right LLVMValues
-	pub fn generate_binary(&mut self, token: &Token, left: ASTNode, right: ASTNode) -> Result<LLVMValue> {
-		let left = self.ast_to_llvm(&left, None)?;
-		let right = self.ast_to_llvm(&right, None)?;
+	pub fn generate_binary(&mut self, token: &Token, left: &ASTNode, right: &ASTNode) -> Result<LLVMValue> {
+		// Logical operators must not evaluate their right side unless needed
+		if token.is_logical() {
+			return self.generate_logical(token, left, right);
+		}
+
+		let (left, right) = if let Token::Equals = token {
+			let left = self.ast_to_llvm(left, None)?;
+			let right = self.generate_value(right, Some(&left.format().rvalue_format()))?;
+			(left, right)
+		} else {
+			let operands = self.generate_operands(&[left, right])?;
+			(operands[0].clone(), operands[1].clone())
+		};
 
 		let out = match token {
 			Token::Asterisk => Ok(self.generate_mul(left, right)?),
 			Token::Minus => Ok(self.generate_sub(left, right)?),
 			Token::Plus => Ok(self.generate_add(left, right)?),
 			Token::Slash => Ok(self.generate_div(left, right)?),
+			Token::Percent => Ok(self.generate_rem(left, right)?),
 			Token::Equals => Ok(self.generate_assign(left, right)?),
 			_ => {
 				// If token is a comparison operator, generate a comparison
@@ -153,7 +380,7 @@ impl Generator {
 		let reg = self.update_virtual_register(1);
 		self.writer.write_mul(&left, &right, reg)?;
 
-		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Integer, true)))
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
 	}
 
 	// Generate LLVMValue for subtraction
@@ -164,7 +391,7 @@ impl Generator {
 		let reg = self.update_virtual_register(1);
 		self.writer.write_sub(&left, &right, reg)?;
 
-		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Integer, true)))
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
 	}
 
 	// Generate LLVMValue for addition
@@ -175,23 +402,512 @@ impl Generator {
 		let reg = self.update_virtual_register(1);
 		self.writer.write_add(&left, &right, reg)?;
 
-		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Integer, true)))
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
 	}
 
 	// Generate LLVMValue for division
 	pub fn generate_div(&mut self, mut left: LLVMValue, mut right: LLVMValue) -> Result<LLVMValue> {
 		self.ensure_arithmetic_operands(&mut left, &mut right)?;
+		self.generate_division_check(&left, &right, "divide")?;
 		let reg = self.update_virtual_register(1);
 		self.writer.write_div(&left, &right, reg)?;
 
-		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Integer, true)))
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
+	}
+
+	// Generate LLVMValue for remainder of division
+	pub fn generate_rem(&mut self, mut left: LLVMValue, mut right: LLVMValue) -> Result<LLVMValue> {
+		self.ensure_arithmetic_operands(&mut left, &mut right)?;
+		self.generate_division_check(&left, &right, "remainder")?;
+		let reg = self.update_virtual_register(1);
+		self.writer.write_rem(&left, &right, reg)?;
+
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
+	}
+
+	// With checked arithmetic, branch to a trap before dividing by zero or dividing the smallest signed value by -1
+	// operation names the traps to use, e.g. "divide" uses divide_by_zero and divide_overflow
+	pub fn generate_division_check(&mut self, left: &LLVMValue, right: &LLVMValue, operation: &str) -> Result<()> {
+		// Float division never traps; it gives an infinity or NaN instead
+		if !self.checked_arithmetic || !left.format().is_integer() {
+			return Ok(());
+		}
+
+		// Shifting keeps the sign bit, so this is the smallest value of the format if it's signed
+		let format = left.format();
+		let min = (i64::MIN >> (64 - format.integer_bits().unwrap_or(64))) as i128;
+
+		// Constant divisors were already checked for zero, and only a signed divisor of -1 can overflow
+		let check_zero = !matches!(right, LLVMValue::Constant(_));
+		let check_overflow = format.is_signed()
+			&& !matches!(right, LLVMValue::Constant(Constant::Integer { value, .. }) if *value != -1)
+			&& !matches!(left, LLVMValue::Constant(Constant::Integer { value, .. }) if *value != min);
+
+		if check_zero {
+			let is_zero = self.update_virtual_register(1);
+			self.writer.write_cmp(right, &LLVMValue::Constant(Constant::Integer { value: 0, format: format.clone() }), is_zero, Token::Equals2.get_pnemonic(false))?;
+			let is_zero = LLVMValue::VirtualRegister(VirtualRegister::new(is_zero.to_string(), RegisterFormat::Boolean, true));
+			self.generate_trap_branch(&is_zero, &format!("{operation}_by_zero"))?;
+		}
+
+		if check_overflow {
+			let is_min = self.update_virtual_register(1);
+			self.writer.write_cmp(left, &LLVMValue::Constant(Constant::Integer { value: min, format: format.clone() }), is_min, Token::Equals2.get_pnemonic(true))?;
+			let is_neg_one = self.update_virtual_register(1);
+			self.writer.write_cmp(right, &LLVMValue::Constant(Constant::Integer { value: -1, format }), is_neg_one, Token::Equals2.get_pnemonic(true))?;
+
+			let is_min = LLVMValue::VirtualRegister(VirtualRegister::new(is_min.to_string(), RegisterFormat::Boolean, true));
+			let is_neg_one = LLVMValue::VirtualRegister(VirtualRegister::new(is_neg_one.to_string(), RegisterFormat::Boolean, true));
+			let overflows = self.update_virtual_register(1);
+			self.writer.write_and(&is_min, &is_neg_one, overflows)?;
+			let overflows = LLVMValue::VirtualRegister(VirtualRegister::new(overflows.to_string(), RegisterFormat::Boolean, true));
+			self.generate_trap_branch(&overflows, &format!("{operation}_overflow"))?;
+		}
+
+		Ok(())
+	}
+
+	// Branch to the named trap if condition holds, otherwise continue in a new block
+	pub fn generate_trap_branch(&mut self, condition: &LLVMValue, trap: &str) -> Result<()> {
+		let trap_label = Label::new(self.update_label_count(1));
+		let continue_label = Label::new(self.update_label_count(1));
+
+		self.writer.write_cond_branch(condition, &trap_label, &continue_label)?;
+		self.writer.write_label(&trap_label)?;
+		self.writer.write_trap(trap)?;
+		self.writer.write_label(&continue_label)?;
+
+		Ok(())
+	}
+
+	// Generate unary operation; '-' negates an integer and '!' inverts a boolean
+	pub fn generate_unary(&mut self, token: &Token, expr: &ASTNode) -> Result<LLVMValue> {
+		let mut val = self.ast_to_llvm(expr, None)?;
+		self.ensure_rvalue(&mut val)?;
+		let reg = self.update_virtual_register(1);
+
+		match token {
+			Token::Minus if val.format().is_signed() || val.format().is_float() => self.writer.write_neg(&val, reg)?,
+			Token::Minus => Err(Error::InvalidArithmeticOperand { received: val.format() })?,
+			Token::Exclamation => {
+				RegisterFormat::Boolean.expect(val.format())?;
+				self.writer.write_not(&val, reg)?;
+			},
+			_ => Err(Error::TerminalTokenExpected { received_token: Some(token.clone()), received_identifier: None })?,
+		}
+
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), val.format(), true)))
+	}
+
+	// Generate a cast of a number to the given type
+	pub fn generate_cast(&mut self, expr: &ASTNode, cast_type: &Type) -> Result<LLVMValue> {
+		let val = self.generate_value(expr, None)?;
+		let fmt = self.get_format_from_type(cast_type)?;
+
+		self.generate_format_cast(val, &fmt)
+	}
+
+	// Convert a value to another format with the instruction given by its cast table
+	pub fn generate_format_cast(&mut self, val: LLVMValue, fmt: &RegisterFormat) -> Result<LLVMValue> {
+		let received = val.format();
+		if received == *fmt {
+			return Ok(val);
+		}
+
+		let Some(instruction) = received.cast_instruction(fmt) else {
+			return Err(Error::InvalidCast { received, expected: fmt.clone() });
+		};
+
+		let reg = VirtualRegister::new(self.update_virtual_register(1).to_string(), fmt.clone(), true);
+		self.writer.write_cast(instruction, &val, &reg)?;
+
+		Ok(LLVMValue::VirtualRegister(reg))
+	}
+
+	// A reference is the address of the storage expr names, kept as a value rather than loaded from
+	pub fn generate_reference(&mut self, expr: &ASTNode, mutable: bool) -> Result<LLVMValue> {
+		let storage = self.ast_to_llvm(expr, None)?;
+		let LLVMValue::VirtualRegister(reg) = &storage else {
+			return Err(Error::LvalueExpected.with_span(&expr.span));
+		};
+		if !matches!(reg.format(), RegisterFormat::Identifier { .. } | RegisterFormat::Pointer { .. }) {
+			return Err(Error::LvalueExpected.with_span(&expr.span));
+		}
+
+		let fmt = RegisterFormat::Reference { referent: Box::new(reg.format().rvalue_format()), mutable };
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.id().to_owned(), fmt, reg.is_local())))
+	}
+
+	// Dereferencing gives a pointer to the referent, which is loaded when used as a value and stored to when assigned
+	// A box is dereferenced the same way, without being moved out of its variable
+	pub fn generate_deref(&mut self, expr: &ASTNode) -> Result<LLVMValue> {
+		let mut reference = self.ast_to_llvm(expr, None)?;
+		self.ensure_rvalue(&mut reference)?;
+		let (LLVMValue::VirtualRegister(reg), RegisterFormat::Reference { referent: pointee, .. } | RegisterFormat::Box { inner: pointee }) = (&reference, reference.format()) else {
+			return Err(Error::InvalidDereference { received: reference.format() });
+		};
+
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.id().to_owned(), pointee.to_pointer(), reg.is_local())))
+	}
+
+	// Allocate heap space for a value and move the value there, giving its address as the box
+	// Number literals take the format of inner_fmt if given; the allocation trap is called if there's no space left
+	pub fn generate_box_new(&mut self, value: &ASTNode, inner_fmt: Option<&RegisterFormat>) -> Result<LLVMValue> {
+		let val = self.generate_value(value, inner_fmt)?;
+		let memory = VirtualRegister::new(self.update_virtual_register(1).to_string(), RegisterFormat::Int8.to_pointer(), true);
+		self.writer.write_malloc(&val.format(), &memory)?;
+		self.allocates_boxes = true;
+
+		let failed = self.update_virtual_register(1);
+		let null = LLVMValue::Constant(Constant::Null { format: RegisterFormat::Int8.to_pointer() });
+		self.writer.write_cmp(&LLVMValue::VirtualRegister(memory.clone()), &null, failed, Token::Equals2.get_pnemonic(false))?;
+		let failed = LLVMValue::VirtualRegister(VirtualRegister::new(failed.to_string(), RegisterFormat::Boolean, true));
+
+		let trap_label = Label::new(self.update_label_count(1));
+		let continue_label = Label::new(self.update_label_count(1));
+		self.writer.write_cond_branch(&failed, &trap_label, &continue_label)?;
+		self.writer.write_label(&trap_label)?;
+		self.writer.write_alloc_trap_call()?;
+		self.writer.write_label(&continue_label)?;
+
+		let boxed = VirtualRegister::new(self.update_virtual_register(1).to_string(), RegisterFormat::Box { inner: Box::new(val.format()) }, true);
+		self.writer.write_cast("bitcast", &LLVMValue::VirtualRegister(memory), &boxed)?;
+		self.writer.write_store(&val, &LLVMValue::VirtualRegister(boxed.clone()))?;
+
+		Ok(LLVMValue::VirtualRegister(boxed))
+	}
+
+	// Free the boxes and vecs held by each storage, latest first; storage one was moved out of holds null
+	// A vec's elements are freed through the address it holds, which is null until something is pushed
+	pub fn generate_frees(&mut self, storage: &[LLVMValue]) -> Result<()> {
+		for held in storage.iter().rev() {
+			let mut owner = held.clone();
+			self.ensure_rvalue(&mut owner)?;
+
+			if let RegisterFormat::Vec { element } = owner.format() {
+				let data = VirtualRegister::new(self.update_virtual_register(1).to_string(), RegisterFormat::Reference { referent: element, mutable: true }, true);
+				self.writer.write_extract_value(&owner, 0, &data)?;
+				owner = LLVMValue::VirtualRegister(data);
+			}
+
+			let memory = VirtualRegister::new(self.update_virtual_register(1).to_string(), RegisterFormat::Int8.to_pointer(), true);
+			self.writer.write_cast("bitcast", &owner, &memory)?;
+			self.writer.write_free(&LLVMValue::VirtualRegister(memory))?;
+		}
+
+		Ok(())
+	}
+
+	// Open a scope for locals; its boxes and vecs are freed when it's closed
+	pub fn push_scope(&mut self) {
+		self.local_symbol_table.push_scope();
+		self.owners.push(Vec::new());
+	}
+
+	pub fn pop_scope(&mut self) -> Result<()> {
+		let owned = self.owners.pop().unwrap_or_default();
+		self.generate_frees(&owned)?;
+		self.local_symbol_table.pop_scope();
+
+		Ok(())
+	}
+
+	// Record that a local holds a box or vec, to be freed when the innermost scope ends
+	pub fn take_ownership(&mut self, symbol: &Symbol) -> Result<()> {
+		if symbol.value().format().owns_memory() {
+			let storage = LLVMValue::VirtualRegister(VirtualRegister::from_symbol(symbol)?);
+			if let Some(owned) = self.owners.last_mut() {
+				owned.push(storage);
+			}
+		}
+
+		Ok(())
+	}
+
+	// Generate an array literal in stack space of its own, giving a pointer to the array
+	// Number literals take the format of element_fmt if given, or else of the first other element
+	pub fn generate_array_literal(&mut self, elements: &[ASTNode], element_fmt: Option<&RegisterFormat>) -> Result<LLVMValue> {
+		let vals = match element_fmt {
+			Some(fmt) => elements.iter().map(|element| self.generate_value(element, Some(fmt))).collect::<Result<Vec<LLVMValue>>>()?,
+			None => self.generate_operands(&elements.iter().collect::<Vec<&ASTNode>>())?,
+		};
+
+		let fmt = RegisterFormat::Array { element: Box::new(vals[0].format()), length: vals.len() };
+		let array = self.generate_stack_value(&fmt)?;
+		for (i, val) in vals.iter().enumerate() {
+			let element = self.generate_element_pointer(&array, &LLVMValue::Constant(Constant::Integer { value: i as i128, format: RegisterFormat::Integer }))?;
+			self.writer.write_store(val, &element)?;
+		}
+
+		Ok(array)
+	}
+
+	// Generate a tuple literal as a value of a literal struct type, inserting its elements one at a time
+	// Number literals take the format given for them in element_fmts if any
+	pub fn generate_tuple_literal(&mut self, elements: &[ASTNode], element_fmts: Option<&[RegisterFormat]>) -> Result<LLVMValue> {
+		let mut vals: Vec<LLVMValue> = Vec::new();
+		for (i, element) in elements.iter().enumerate() {
+			vals.push(self.generate_value(element, element_fmts.and_then(|element_fmts| element_fmts.get(i)))?);
+		}
+
+		let fmt = RegisterFormat::Tuple { elements: vals.iter().map(|val| val.format()).collect() };
+		let mut tuple = LLVMValue::Constant(Constant::Undefined { format: fmt.clone() });
+		for (i, val) in vals.iter().enumerate() {
+			let reg = VirtualRegister::new(self.update_virtual_register(1).to_string(), fmt.clone(), true);
+			self.writer.write_insert_value(&tuple, val, i, &reg)?;
+			tuple = LLVMValue::VirtualRegister(reg);
+		}
+
+		Ok(tuple)
+	}
+
+	// Allocate stack space for an array, struct or enum that isn't stored in a variable
+	// Its name starts with a dot, which no local's name can, so it can't clash with one
+	pub fn generate_stack_value(&mut self, fmt: &RegisterFormat) -> Result<LLVMValue> {
+		self.stack_values += 1;
+		let array = VirtualRegister::new(format!(".stack.{}", self.stack_values), fmt.to_pointer(), true);
+		self.writer.write_local_alloc(&array, fmt)?;
+
+		Ok(LLVMValue::VirtualRegister(array))
+	}
+
+	// Get a pointer to the array, struct or enum node evaluates to
+	// Values that aren't stored anywhere, e.g. ones returned by a function, are stored on the stack
+	pub fn generate_aggregate_pointer(&mut self, node: &ASTNode) -> Result<LLVMValue> {
+		let val = self.ast_to_llvm(node, None)?;
+		if !matches!(val.format(), RegisterFormat::Array { .. } | RegisterFormat::Struct { .. } | RegisterFormat::Enum { .. }) {
+			return Ok(val);
+		}
+
+		let stored = self.generate_stack_value(&val.format())?;
+		self.writer.write_store(&val, &stored)?;
+
+		Ok(stored)
+	}
+
+	// Generate a pointer to an element of an array or vec, which is loaded when used as a value and stored to when assigned
+	pub fn generate_index(&mut self, array: &ASTNode, index: &ASTNode) -> Result<LLVMValue> {
+		let array = self.generate_aggregate_pointer(array)?;
+		let array_fmt = array.format().rvalue_format();
+		if !matches!(array_fmt, RegisterFormat::Array { .. } | RegisterFormat::Vec { .. }) {
+			return Err(Error::InvalidIndexTarget { received: array_fmt });
+		}
+
+		// Indexes of any integer format are used as an int
+		let index = self.generate_value(index, Some(&RegisterFormat::Integer))?;
+		if !index.format().is_integer() {
+			return Err(Error::InvalidIndex { received: index.format() });
+		}
+		let index = self.generate_format_cast(index, &RegisterFormat::Integer)?;
+
+		// A vec's elements are found through the address it holds, and its length is only known at runtime
+		if let RegisterFormat::Vec { element } = array_fmt {
+			let data = self.generate_vec_field(&array, 0, RegisterFormat::Reference { referent: element.clone(), mutable: true })?;
+			if self.checked_indexing {
+				let length = self.generate_vec_field(&array, 1, RegisterFormat::Integer)?;
+				self.generate_bounds_check(&index, &length)?;
+			}
+
+			let reg = VirtualRegister::new(self.update_virtual_register(1).to_string(), element.to_pointer(), true);
+			self.writer.write_offset_pointer(&data, &index, &reg)?;
+			return Ok(LLVMValue::VirtualRegister(reg));
+		}
+
+		// Constant indexes into arrays were already checked against the length
+		if let (true, RegisterFormat::Array { length, .. }) = (self.checked_indexing, array_fmt) {
+			if !matches!(index, LLVMValue::Constant(_)) {
+				self.generate_bounds_check(&index, &LLVMValue::Constant(Constant::Integer { value: length as i128, format: RegisterFormat::Integer }))?;
+			}
+		}
+
+		self.generate_element_pointer(&array, &index)
+	}
+
+	// Get a pointer to the storage of the vec node refers to, which is either held where node names or pointed to
+	// by the reference node evaluates to
+	pub fn generate_vec_pointer(&mut self, node: &ASTNode) -> Result<LLVMValue> {
+		let mut vec = self.ast_to_llvm(node, None)?;
+		if let RegisterFormat::Reference { .. } = vec.format().rvalue_format() {
+			self.ensure_rvalue(&mut vec)?;
+		}
+
+		match (&vec, vec.format()) {
+			(LLVMValue::VirtualRegister(reg), RegisterFormat::Reference { referent, .. }) if matches!(*referent, RegisterFormat::Vec { .. }) => {
+				Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.id().to_owned(), referent.to_pointer(), reg.is_local())))
+			},
+			(_, RegisterFormat::Identifier { id_type: fmt } | RegisterFormat::Pointer { pointee: fmt }) if matches!(*fmt, RegisterFormat::Vec { .. }) => Ok(vec),
+			(_, received) => Err(Error::InvalidIterable { received }),
+		}
+	}
+
+	// Load a field of the vec that vec points to, i.e. the address of its elements, its length or its capacity
+	pub fn generate_vec_field(&mut self, vec: &LLVMValue, index: usize, fmt: RegisterFormat) -> Result<LLVMValue> {
+		let reg = VirtualRegister::new(self.update_virtual_register(1).to_string(), fmt.to_pointer(), true);
+		self.writer.write_field_pointer(vec, index, &reg)?;
+
+		let mut field = LLVMValue::VirtualRegister(reg);
+		self.ensure_rvalue(&mut field)?;
+
+		Ok(field)
+	}
+
+	// Call a method of a vec where it's held; push and pop call the helper functions for its element format,
+	// while len reads its length directly
+	pub fn generate_method_call(&mut self, expr: &ASTNode, method: &str, args: &[ASTNode]) -> Result<LLVMValue> {
+		let vec = self.generate_vec_pointer(expr)?;
+		let fmt = vec.format().rvalue_format();
+		let (Some((signature, _)), RegisterFormat::Vec { element }) = (fmt.method(method), &fmt) else {
+			return Err(Error::UnknownMethod { received: fmt.clone(), name: method.to_owned() });
+		};
+
+		if method == "len" {
+			return self.generate_vec_field(&vec, 1, RegisterFormat::Integer);
+		}
+
+		let mut arg_vals = vec![vec.clone()];
+		for (arg, param) in args.iter().zip(signature.params()) {
+			arg_vals.push(self.generate_value(arg, Some(param))?);
+		}
+
+		if !self.vec_elements.contains(element) {
+			self.vec_elements.push(*element.clone());
+		}
+
+		let ret_reg_num = self.update_virtual_register(match signature.return_fmt() { RegisterFormat::Void => 0, _ => 1 });
+		let ret_reg = LLVMValue::VirtualRegister(VirtualRegister::new(ret_reg_num.to_string(), signature.return_fmt().to_owned(), true));
+		self.writer.write_function_call(&Writer::vec_helper(method, element), &arg_vals, &ret_reg)?;
+
+		Ok(ret_reg)
+	}
+
+	// Index into the array behind array without loading the element, so it can be read, assigned or referenced
+	pub fn generate_element_pointer(&mut self, array: &LLVMValue, index: &LLVMValue) -> Result<LLVMValue> {
+		let RegisterFormat::Array { element, .. } = array.format().rvalue_format() else {
+			return Err(Error::InvalidIndexTarget { received: array.format().rvalue_format() });
+		};
+
+		let reg = VirtualRegister::new(self.update_virtual_register(1).to_string(), element.to_pointer(), true);
+		self.writer.write_element_pointer(array, index, &reg)?;
+
+		Ok(LLVMValue::VirtualRegister(reg))
+	}
+
+	// Generate a struct literal in stack space of its own, giving a pointer to the struct
+	// Fields are evaluated in the order they're given, rather than the order they were declared in
+	pub fn generate_struct_literal(&mut self, name: &str, fields: &[(String, ASTNode)]) -> Result<LLVMValue> {
+		let fmt = self.get_format_from_type(&Type::Named { type_name: name.to_owned() })?;
+		let strct = self.generate_stack_value(&fmt)?;
+
+		for (field, value) in fields {
+			let Some((_, field_fmt)) = fmt.field(field) else {
+				return Err(Error::UnknownField { struct_fmt: fmt.clone(), name: field.to_owned() });
+			};
+
+			let val = self.generate_value(value, Some(field_fmt))?;
+			let field_pointer = self.generate_field_pointer(&strct, field)?;
+			self.writer.write_store(&val, &field_pointer)?;
+		}
+
+		Ok(strct)
+	}
+
+	// Generate a pointer to a field of a struct, which is loaded when used as a value and stored to when assigned
+	pub fn generate_field_access(&mut self, expr: &ASTNode, field: &str) -> Result<LLVMValue> {
+		let strct = self.generate_aggregate_pointer(expr)?;
+
+		self.generate_field_pointer(&strct, field)
+	}
+
+	// Get a pointer to a field of the struct that strct points to
+	pub fn generate_field_pointer(&mut self, strct: &LLVMValue, field: &str) -> Result<LLVMValue> {
+		let fmt = strct.format().rvalue_format();
+		let Some((index, field_fmt)) = fmt.field(field) else {
+			return Err(Error::UnknownField { struct_fmt: fmt.clone(), name: field.to_owned() });
+		};
+
+		let reg = VirtualRegister::new(self.update_virtual_register(1).to_string(), field_fmt.to_pointer(), true);
+		self.writer.write_field_pointer(strct, index, &reg)?;
+
+		Ok(LLVMValue::VirtualRegister(reg))
+	}
+
+	// Generate a variant of an enum in stack space of its own, giving a pointer to the enum
+	// The tag is set to the variant's index, and the payload is written through the variant's payload struct
+	pub fn generate_variant_literal(&mut self, enum_name: &str, variant: &str, values: &[ASTNode]) -> Result<LLVMValue> {
+		let fmt = self.get_format_from_type(&Type::Named { type_name: enum_name.to_owned() })?;
+		let Some((tag, payload_fmts)) = fmt.variant(variant) else {
+			return Err(Error::UnknownVariant { enum_fmt: fmt.clone(), name: variant.to_owned() });
+		};
+		let payload_fmts = payload_fmts.clone();
+
+		let enm = self.generate_stack_value(&fmt)?;
+		let tag_pointer = self.generate_tag_pointer(&enm)?;
+		self.writer.write_store(&LLVMValue::Constant(Constant::Integer { value: tag as i128, format: RegisterFormat::Int32 }), &tag_pointer)?;
+
+		if !values.is_empty() {
+			let payload = self.generate_payload_pointer(&enm, tag)?;
+			for (i, (value, value_fmt)) in values.iter().zip(payload_fmts.iter()).enumerate() {
+				let val = self.generate_value(value, Some(value_fmt))?;
+				let value_pointer = self.generate_field_pointer(&payload, &i.to_string())?;
+				self.writer.write_store(&val, &value_pointer)?;
+			}
+		}
+
+		Ok(enm)
+	}
+
+	// Get a pointer to the tag of the enum that enm points to
+	pub fn generate_tag_pointer(&mut self, enm: &LLVMValue) -> Result<LLVMValue> {
+		let reg = VirtualRegister::new(self.update_virtual_register(1).to_string(), RegisterFormat::Int32.to_pointer(), true);
+		self.writer.write_field_pointer(enm, 0, &reg)?;
+
+		Ok(LLVMValue::VirtualRegister(reg))
+	}
+
+	// Get a pointer to the payload of the enum that enm points to, as the payload struct of the variant at tag
+	pub fn generate_payload_pointer(&mut self, enm: &LLVMValue, tag: usize) -> Result<LLVMValue> {
+		let fmt = enm.format().rvalue_format();
+		let Some(payload_fmt) = fmt.payload_format(tag) else {
+			return Err(Error::EnumExpected { received: fmt });
+		};
+
+		let storage_fmt = RegisterFormat::Array { element: Box::new(RegisterFormat::Integer), length: fmt.payload_words() };
+		let storage = VirtualRegister::new(self.update_virtual_register(1).to_string(), storage_fmt.to_pointer(), true);
+		self.writer.write_field_pointer(enm, 1, &storage)?;
+
+		let payload = VirtualRegister::new(self.update_virtual_register(1).to_string(), payload_fmt.to_pointer(), true);
+		self.writer.write_cast("bitcast", &LLVMValue::VirtualRegister(storage), &payload)?;
+
+		Ok(LLVMValue::VirtualRegister(payload))
+	}
+
+	// Branch to the index trap unless index is less than length, otherwise continue in a new block
+	pub fn generate_bounds_check(&mut self, index: &LLVMValue, length: &LLVMValue) -> Result<()> {
+		// Negative indexes are larger than any length when compared as unsigned, so one comparison checks both ends
+		let out_of_bounds = self.update_virtual_register(1);
+		self.writer.write_cmp(index, length, out_of_bounds, Token::GreaterThanEqual.get_pnemonic(false))?;
+		let out_of_bounds = LLVMValue::VirtualRegister(VirtualRegister::new(out_of_bounds.to_string(), RegisterFormat::Boolean, true));
+
+		let trap_label = Label::new(self.update_label_count(1));
+		let continue_label = Label::new(self.update_label_count(1));
+		self.writer.write_cond_branch(&out_of_bounds, &trap_label, &continue_label)?;
+		self.writer.write_label(&trap_label)?;
+		self.writer.write_index_trap_call(index, length)?;
+		self.writer.write_label(&continue_label)?;
+
+		Ok(())
 	}
 
 	// Generate LLVMValue for assignment of left = right
 	pub fn generate_assign(&mut self, left: LLVMValue, mut right: LLVMValue) -> Result<LLVMValue> {
 		// Make right an operand, assign it to left, and return left for use again
 		self.ensure_rvalue(&mut right)?;
-		left.format().expect(right.format())?;
+		left.format().rvalue_format().expect_convertible(right.format())?;
+
+		// A box or vec being replaced is freed first; its storage is null if it was moved out of
+		if left.format().rvalue_format().owns_memory() {
+			self.generate_frees(std::slice::from_ref(&left))?;
+		}
 		self.writer.write_store(&right, &left)?;
 		Ok(left)
 	}
@@ -200,31 +916,81 @@ impl Generator {
 	pub fn generate_comparison(&mut self, operator: Token, mut left: LLVMValue, mut right: LLVMValue) -> Result<LLVMValue> {
 		// Make sure both sides are operands, compare them, and store the result as a boolean register
 		self.ensure_comparison_operands(&mut left, &mut right, &operator)?;
-		let pnemonic = operator.get_pnemonic();
+		let pnemonic = if left.format().is_float() {
+			operator.get_float_pnemonic()
+		} else {
+			operator.get_pnemonic(left.format().is_signed())
+		};
 		let reg = self.update_virtual_register(1);
 		self.writer.write_cmp(&left, &right, reg, pnemonic)?;
 		
 		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Boolean, true)))
 	}
 
+	// Generate short-circuiting '&&' or '||'
+	// The right side is only evaluated when the left side doesn't decide the result, and a phi node
+	// picks between the left side's deciding value and the right side's value
+	pub fn generate_logical(&mut self, operator: &Token, left: &ASTNode, right: &ASTNode) -> Result<LLVMValue> {
+		let left_label = Label::new(self.update_label_count(1));
+		let right_label = Label::new(self.update_label_count(1));
+		let right_tail_label = Label::new(self.update_label_count(1));
+		let tail_label = Label::new(self.update_label_count(1));
+
+		let mut left_llvm = self.ast_to_llvm(left, None)?;
+		self.ensure_rvalue(&mut left_llvm)?;
+		RegisterFormat::Boolean.expect(left_llvm.format())?;
+
+		// The left side may contain its own branches, so end it in a known label for the phi node
+		self.writer.write_branch(&left_label)?;
+		self.writer.write_label(&left_label)?;
+		let short_circuit = match operator {
+			Token::Ampersand2 => {
+				self.writer.write_cond_branch(&left_llvm, &right_label, &tail_label)?;
+				false
+			},
+			Token::Pipe2 => {
+				self.writer.write_cond_branch(&left_llvm, &tail_label, &right_label)?;
+				true
+			},
+			_ => Err(Error::BinaryOperatorExpected { received: operator.clone() })?,
+		};
+
+		self.writer.write_label(&right_label)?;
+		let mut right_llvm = self.ast_to_llvm(right, None)?;
+		self.ensure_rvalue(&mut right_llvm)?;
+		RegisterFormat::Boolean.expect(right_llvm.format())?;
+		self.writer.write_branch(&right_tail_label)?;
+		self.writer.write_label(&right_tail_label)?;
+		self.writer.write_branch(&tail_label)?;
+
+		self.writer.write_label(&tail_label)?;
+		let reg = self.update_virtual_register(1);
+		let incoming = [
+			(LLVMValue::Constant(Constant::Boolean(short_circuit)), &left_label),
+			(right_llvm, &right_tail_label),
+		];
+		self.writer.write_phi(&RegisterFormat::Boolean, &incoming, reg)?;
+
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Boolean, true)))
+	}
+
 	pub fn generate_let(&mut self, name: &String, val_type: &Option<Type>, value: &Option<Box<ASTNode>>) -> Result<LLVMValue> {
-		if let Ok(_) = self.local_symbol_table.get(name) {
+		if self.local_symbol_table.is_declared_in_scope(name) {
 			return Err(Error::SymbolDeclared { name: name.to_owned() });
 		}
 
 		if let Some(val) = value {
-			let assigned_llvm = self.ast_to_llvm(&val, None)?;
+			let declared_fmt = val_type.as_ref().map(|v| self.get_format_from_type(v)).transpose()?;
+			let assigned_llvm = self.generate_value(val, declared_fmt.as_ref())?;
 			// If val_type is not given, use implicit format
-			let reg_fmt = match val_type {
-				Some(v) => self.get_format_from_type(v)?,
-				None => assigned_llvm.format(),
-			};
+			let reg_fmt = declared_fmt.unwrap_or_else(|| assigned_llvm.format());
 			if !assigned_llvm.format().can_convert_to(&reg_fmt) {
 				Err(Error::InvalidAssignment { received: assigned_llvm.format().to_owned(), expected: reg_fmt.clone() })?;
 			}
 			let (symbol, reg) = self.local_symbol_table.create_local(name, &reg_fmt);
 			self.writer.write_local_alloc(&reg, &reg_fmt)?;
 			self.writer.write_store(&assigned_llvm, &LLVMValue::VirtualRegister(reg))?;
+			self.take_ownership(&symbol)?;
 			self.local_symbol_table.insert(symbol);
 		} else {
 			// No value assigned; if value type specified, assign that type; else, assign an int
@@ -234,66 +1000,138 @@ impl Generator {
 			};
 			let (symbol, reg) = self.local_symbol_table.create_local(name, &reg_fmt);
 			self.writer.write_local_alloc(&reg, &reg_fmt)?;
+
+			// Boxes and vecs hold null until they're assigned, so they can be freed either way
+			if reg_fmt.owns_memory() {
+				self.writer.write_store(&LLVMValue::Constant(Constant::Null { format: reg_fmt.clone() }), &LLVMValue::VirtualRegister(reg))?;
+			}
+			self.take_ownership(&symbol)?;
 			self.local_symbol_table.insert(symbol);
 		}
 
 		Ok(LLVMValue::None)
 	}
 
-	// Generate if statement
-	pub fn generate_if(&mut self, expr: &ASTNode, block: &Vec<ASTNode>, else_block: &Option<Vec<ASTNode>>, expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
-		let mut expr_llvm = self.ast_to_llvm(expr, None)?;
-		self.ensure_rvalue(&mut expr_llvm)?;
-		expr_llvm.format().expect(RegisterFormat::Boolean)?;
+	// Destructure a tuple into a local for each element that isn't bound to '_'
+	pub fn generate_let_tuple(&mut self, names: &[Option<String>], val_type: &Option<Type>, value: &ASTNode) -> Result<LLVMValue> {
+		let declared_fmt = val_type.as_ref().map(|v| self.get_format_from_type(v)).transpose()?;
+		let tuple = self.generate_value(value, declared_fmt.as_ref())?;
+		let element_fmts = match tuple.format() {
+			RegisterFormat::Tuple { elements } if elements.len() == names.len() => elements,
+			received => return Err(Error::InvalidDestructure { count: names.len(), received }),
+		};
 
-		// Generate a label for if branch.
-		// If else is present, generate an else label, emit conditional branch with body label and else label, and parse blocks
-		// Else, emit conditional branch with body label and tail label, and parse only body block
-		let body_label = Label::new(self.update_label_count(1));
-		if let Some(else_block) = else_block {
-			let else_label = Label::new(self.update_label_count(1));
-			let tail_label = Label::new(self.update_label_count(1));
+		for (i, (name, fmt)) in names.iter().zip(element_fmts.iter()).enumerate() {
+			let Some(name) = name else {
+				continue;
+			};
 
-			self.writer.write_cond_branch(&expr_llvm, &body_label, &else_label)?;
+			if self.local_symbol_table.is_declared_in_scope(name) {
+				return Err(Error::SymbolDeclared { name: name.to_owned() });
+			}
 
-			// Write body portion of if statement
-			self.writer.write_label(&body_label)?;
+			let element = VirtualRegister::new(self.update_virtual_register(1).to_string(), fmt.clone(), true);
+			self.writer.write_extract_value(&tuple, i, &element)?;
 
-			for block_statement in block {
-				self.ast_to_llvm(block_statement, expected_fmt.to_owned())?;
-			}
+			let (symbol, reg) = self.local_symbol_table.create_local(name, fmt);
+			self.writer.write_local_alloc(&reg, fmt)?;
+			self.writer.write_store(&LLVMValue::VirtualRegister(element), &LLVMValue::VirtualRegister(reg))?;
+			self.local_symbol_table.insert(symbol);
+		}
 
-			self.writer.write_branch(&tail_label)?;
-			
-			// Write else portion
-			self.writer.write_label(&else_label)?;
-			for else_statement in else_block {
-				self.ast_to_llvm(else_statement, expected_fmt.to_owned())?;
+		Ok(LLVMValue::None)
+	}
+
+	// Generate the statements of a block in a scope of their own
+	// A box or vec a statement evaluates to isn't held by anything, e.g. one returned by a call whose result isn't used,
+	// so it's freed right away
+	pub fn generate_block(&mut self, block: &[ASTNode], expected_fmt: &Option<RegisterFormat>) -> Result<()> {
+		self.push_scope();
+		for statement in block {
+			let val = self.ast_to_llvm(statement, expected_fmt.to_owned())?;
+			if val.format().owns_memory() {
+				self.generate_frees(&[val])?;
 			}
+		}
 
-			self.writer.write_branch(&tail_label)?;
-			self.writer.write_label(&tail_label)?;
+		self.pop_scope()
+	}
+
+	// Generate a global variable, initialized with its constant value
+	// Constants aren't stored anywhere; their value is substituted wherever they're used
+	pub fn generate_global(&mut self, name: &str, val_type: Option<&Type>, value: Option<&ASTNode>, constant: bool) -> Result<LLVMValue> {
+		let declared_fmt = val_type.map(|v| self.get_format_from_type(v)).transpose()?;
+		let initial = match (value, &declared_fmt) {
+			(Some(val), Some(fmt)) if constant::is_number_literal(val) => Some(constant::evaluate_literal(val, fmt).ok_or(Error::ConstantExpected)?),
+			(Some(val), _) => Some(constant::evaluate(val, &|name| named_constant(&self.local_symbol_table, &self.global_symbol_table, name)).ok_or(Error::ConstantExpected)?),
+			(None, _) => None,
+		};
+		let reg_fmt = match declared_fmt {
+			Some(fmt) => fmt,
+			None => initial.as_ref().map(|val| val.format()).unwrap_or(RegisterFormat::Integer),
+		};
+		let initial = match initial {
+			Some(val) => val,
+			None => Constant::zero(&reg_fmt).ok_or(Error::ConstantExpected)?,
+		};
+		if !initial.format().can_convert_to(&reg_fmt) {
+			return Err(Error::InvalidAssignment { received: initial.format(), expected: reg_fmt });
+		}
+
+		if constant {
+			let symbol = self.global_symbol_table.create_constant(name, &initial);
+			self.global_symbol_table.insert(symbol);
 		} else {
-			// No else statement
-			let tail_label = Label::new(self.update_label_count(1));
+			let (symbol, reg) = self.global_symbol_table.create_global(name, &reg_fmt);
+			self.writer.write_global(&reg, &initial)?;
+			self.global_symbol_table.insert(symbol);
+		}
+
+		Ok(LLVMValue::None)
+	}
+
+	// Generate if statement; an else if chain shares a single tail label
+	pub fn generate_if(&mut self, expr: &ASTNode, block: &[ASTNode], else_block: &Option<Vec<ASTNode>>, expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
+		let tail_label = Label::new(self.update_label_count(1));
+		self.generate_if_arm(expr, block, else_block, &tail_label, expected_fmt)?;
+		self.writer.write_label(&tail_label)?;
 
-			self.writer.write_cond_branch(&expr_llvm, &body_label, &tail_label)?;
+		Ok(LLVMValue::None)
+	}
 
-			// Body portion
-			self.writer.write_label(&body_label)?;
+	// Generate one arm of an if statement, branching to tail_label once it's done
+	// If else is present, branch to an else label on false and generate the else block there
+	// An else block holding only an if statement is generated as the next arm of the same chain
+	pub fn generate_if_arm(&mut self, expr: &ASTNode, block: &[ASTNode], else_block: &Option<Vec<ASTNode>>, tail_label: &Label, expected_fmt: &Option<RegisterFormat>) -> Result<()> {
+		let mut expr_llvm = self.ast_to_llvm(expr, None)?;
+		self.ensure_rvalue(&mut expr_llvm)?;
+		expr_llvm.format().expect(RegisterFormat::Boolean)?;
 
-			for block_statement in block {
-				self.ast_to_llvm(block_statement, None)?;
-			}
+		let body_label = Label::new(self.update_label_count(1));
+		let else_label = else_block.as_ref().map(|_| Label::new(self.update_label_count(1)));
+		self.writer.write_cond_branch(&expr_llvm, &body_label, else_label.as_ref().unwrap_or(tail_label))?;
 
-			self.writer.write_branch(&tail_label)?;
-			self.writer.write_label(&tail_label)?;
+		// Write body portion of if statement
+		self.writer.write_label(&body_label)?;
+		self.generate_block(block, expected_fmt)?;
+		self.writer.write_branch(tail_label)?;
+
+		// Write else portion
+		if let (Some(else_block), Some(else_label)) = (else_block, &else_label) {
+			self.writer.write_label(else_label)?;
+
+			if let [ASTNode { kind: NodeKind::If { expr, block, else_block }, span }] = else_block.as_slice() {
+				self.generate_if_arm(expr, block, else_block, tail_label, expected_fmt).map_err(|error| error.with_span(span))?;
+			} else {
+				self.generate_block(else_block, expected_fmt)?;
+				self.writer.write_branch(tail_label)?;
+			}
 		}
 
-		Ok(LLVMValue::None)
+		Ok(())
 	}
 
-	pub fn generate_while(&mut self, expr: &ASTNode, block: &Vec<ASTNode>, expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
+	pub fn generate_while(&mut self, expr: &ASTNode, block: &[ASTNode], expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
 		let cond_label = Label::new(self.update_label_count(1));
 		let body_label = Label::new(self.update_label_count(1));
 		let tail_label = Label::new(self.update_label_count(1));
@@ -305,72 +1143,339 @@ impl Generator {
 		expr_llvm.format().expect(RegisterFormat::Boolean)?;
 		self.writer.write_cond_branch(&expr_llvm, &body_label, &tail_label)?;
 
-		// Write body
+		// Write body, with continue going back to the condition and break leaving the loop
 		self.writer.write_label(&body_label)?;
-		for body_statement in block {
-			self.ast_to_llvm(body_statement, expected_fmt.to_owned())?;
+		self.loop_labels.push((cond_label.clone(), tail_label.clone(), self.owners.len()));
+		self.generate_block(block, expected_fmt)?;
+		self.loop_labels.pop();
+		self.writer.write_branch(&cond_label)?;
+
+		// Tail
+		self.writer.write_label(&tail_label)?;
+
+		Ok(LLVMValue::None)
+	}
+
+	// Generate a for loop over a range, counting up by step
+	// The range is checked once before the body; continue branches to the step label, which decides whether another
+	// step fits in the range before taking it
+	#[allow(clippy::too_many_arguments)]
+	pub fn generate_for(&mut self, name: &str, start: &ASTNode, end: &ASTNode, inclusive: bool, step: &Option<Box<ASTNode>>, block: &[ASTNode], expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
+		let body_label = Label::new(self.update_label_count(1));
+		let step_label = Label::new(self.update_label_count(1));
+		let next_label = Label::new(self.update_label_count(1));
+		let tail_label = Label::new(self.update_label_count(1));
+
+		// Bounds and step are evaluated once, before the loop starts, and share the loop variable's format
+		let bound_nodes: Vec<&ASTNode> = [Some(start), Some(end), step.as_deref()].into_iter().flatten().collect();
+		let bounds = self.generate_operands(&bound_nodes)?;
+		let loop_fmt = bounds[0].format();
+		if !loop_fmt.is_integer() {
+			return Err(Error::UnexpectedFormat { expected: RegisterFormat::Integer, received: loop_fmt });
+		}
+		for bound in &bounds[1..] {
+			loop_fmt.expect(bound.format())?;
 		}
+		let step_llvm = bounds.get(2).cloned().unwrap_or(LLVMValue::Constant(Constant::Integer { value: 1, format: loop_fmt.clone() }));
+
+		// The loop variable is in a scope of its own around the body
+		self.push_scope();
+		let (symbol, reg) = self.local_symbol_table.create_local(name, &loop_fmt);
+		let counter = LLVMValue::VirtualRegister(reg.clone());
+		self.writer.write_local_alloc(&reg, &loop_fmt)?;
+		self.writer.write_store(&bounds[0], &counter)?;
+		self.local_symbol_table.insert(symbol);
+
+		let operator = if inclusive { Token::LessThanEqual } else { Token::LessThan };
+		let in_range = self.generate_comparison(operator.clone(), counter.clone(), bounds[1].clone())?;
+		self.writer.write_cond_branch(&in_range, &body_label, &tail_label)?;
+
+		// Write body, with continue going to the step and break leaving the loop
+		self.writer.write_label(&body_label)?;
+		self.loop_labels.push((step_label.clone(), tail_label.clone(), self.owners.len()));
+		self.generate_block(block, expected_fmt)?;
+		self.loop_labels.pop();
+		self.writer.write_branch(&step_label)?;
+
+		// Step, leaving the loop rather than stepping past the end of the range, so the loop variable can't overflow
+		// The body may have assigned the loop variable, so it's checked against the end again first; while it's in
+		// range, the distance to the end can't overflow when taken as unsigned, even for signed formats
+		self.writer.write_label(&step_label)?;
+		let mut current = counter.clone();
+		self.ensure_rvalue(&mut current)?;
+		let in_range = self.generate_comparison(operator, current.clone(), bounds[1].clone())?;
+		let reg = self.update_virtual_register(1);
+		self.writer.write_arithmetic("sub", &bounds[1], &current, reg)?;
+		let remaining = LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), loop_fmt.clone(), true));
+		let reg = self.update_virtual_register(1);
+		self.writer.write_cmp(&remaining, &step_llvm, reg, String::from(if inclusive { "uge" } else { "ugt" }))?;
+		let fits = LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Boolean, true));
+		let reg = self.update_virtual_register(1);
+		self.writer.write_and(&in_range, &fits, reg)?;
+		let steps = LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Boolean, true));
+		self.writer.write_cond_branch(&steps, &next_label, &tail_label)?;
+
+		self.writer.write_label(&next_label)?;
+		let next = self.generate_add(current, step_llvm)?;
+		self.writer.write_store(&next, &counter)?;
+		self.writer.write_branch(&body_label)?;
+
+		// Tail
+		self.writer.write_label(&tail_label)?;
+		self.pop_scope()?;
+
+		Ok(LLVMValue::None)
+	}
+
+	// Generate a for loop over the elements of a vec, copying each into the loop variable
+	// The vec's length and address are read again every iteration, since the body may push to or pop from it
+	pub fn generate_for_each(&mut self, name: &str, vec: &ASTNode, block: &[ASTNode], expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
+		let cond_label = Label::new(self.update_label_count(1));
+		let body_label = Label::new(self.update_label_count(1));
+		let step_label = Label::new(self.update_label_count(1));
+		let tail_label = Label::new(self.update_label_count(1));
+
+		let vec = self.generate_vec_pointer(vec)?;
+		let RegisterFormat::Vec { element } = vec.format().rvalue_format() else {
+			return Err(Error::InvalidIterable { received: vec.format().rvalue_format() });
+		};
+
+		// The position of the current element is kept next to the loop variable, in the scope around the body
+		self.push_scope();
+		let position = self.generate_stack_value(&RegisterFormat::Integer)?;
+		self.writer.write_store(&LLVMValue::Constant(Constant::Integer { value: 0, format: RegisterFormat::Integer }), &position)?;
+		let (symbol, reg) = self.local_symbol_table.create_local(name, &element);
+		let current = LLVMValue::VirtualRegister(reg.clone());
+		self.writer.write_local_alloc(&reg, &element)?;
+		self.local_symbol_table.insert(symbol);
+
+		self.writer.write_branch(&cond_label)?;
+		self.writer.write_label(&cond_label)?;
+		let length = self.generate_vec_field(&vec, 1, RegisterFormat::Integer)?;
+		let in_range = self.generate_comparison(Token::LessThan, position.clone(), length)?;
+		self.writer.write_cond_branch(&in_range, &body_label, &tail_label)?;
+
+		// Copy the current element into the loop variable, then write body, with continue going to the step and break leaving the loop
+		self.writer.write_label(&body_label)?;
+		let data = self.generate_vec_field(&vec, 0, RegisterFormat::Reference { referent: element.clone(), mutable: true })?;
+		let mut index = position.clone();
+		self.ensure_rvalue(&mut index)?;
+		let element_reg = VirtualRegister::new(self.update_virtual_register(1).to_string(), element.to_pointer(), true);
+		self.writer.write_offset_pointer(&data, &index, &element_reg)?;
+		let mut element_val = LLVMValue::VirtualRegister(element_reg);
+		self.ensure_rvalue(&mut element_val)?;
+		self.writer.write_store(&element_val, &current)?;
+
+		self.loop_labels.push((step_label.clone(), tail_label.clone(), self.owners.len()));
+		self.generate_block(block, expected_fmt)?;
+		self.loop_labels.pop();
+		self.writer.write_branch(&step_label)?;
+
+		// Step
+		self.writer.write_label(&step_label)?;
+		let next = self.generate_add(position.clone(), LLVMValue::Constant(Constant::Integer { value: 1, format: RegisterFormat::Integer }))?;
+		self.writer.write_store(&next, &position)?;
 		self.writer.write_branch(&cond_label)?;
 
 		// Tail
 		self.writer.write_label(&tail_label)?;
+		self.pop_scope()?;
 
 		Ok(LLVMValue::None)
 	}
 
+	// Generate a match statement as a switch on an integer or on the tag of an enum, with a label for each arm
+	// Matches without a '_' arm cover every value, so the switch's default is unreachable
+	pub fn generate_match(&mut self, expr: &ASTNode, arms: &[MatchArm], expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
+		let arm_labels: Vec<Label> = arms.iter().map(|_| Label::new(self.update_label_count(1))).collect();
+		let unreachable_label = Label::new(self.update_label_count(1));
+		let tail_label = Label::new(self.update_label_count(1));
+
+		// Enums are kept as a pointer, so their payload can be read once the arm is known
+		let val = self.generate_aggregate_pointer(expr)?;
+		let fmt = val.format().rvalue_format();
+		let mut switched = if let RegisterFormat::Enum { .. } = fmt {
+			self.generate_tag_pointer(&val)?
+		} else {
+			val.clone()
+		};
+		self.ensure_rvalue(&mut switched)?;
+
+		let mut cases: Vec<(LLVMValue, &Label)> = Vec::new();
+		let mut default_label = &unreachable_label;
+		for (arm, label) in arms.iter().zip(arm_labels.iter()) {
+			match &arm.pattern {
+				Pattern::Variant { variant, .. } => {
+					let Some((tag, _)) = fmt.variant(variant) else {
+						return Err(Error::UnknownVariant { enum_fmt: fmt.clone(), name: variant.to_owned() }.with_span(&arm.span));
+					};
+					cases.push((LLVMValue::Constant(Constant::Integer { value: tag as i128, format: RegisterFormat::Int32 }), label));
+				},
+				Pattern::Integer(value) => cases.push((LLVMValue::Constant(Constant::Integer { value: *value, format: fmt.clone() }), label)),
+				Pattern::Wildcard => default_label = label,
+			}
+		}
+		self.writer.write_switch(&switched, default_label, &cases)?;
+
+		for (arm, label) in arms.iter().zip(arm_labels.iter()) {
+			self.writer.write_label(label)?;
+
+			// Bound values are copied out of the payload into locals scoped to the arm
+			self.push_scope();
+			if let Pattern::Variant { variant, bindings, .. } = &arm.pattern {
+				if bindings.iter().any(|binding| binding.is_some()) {
+					let tag = fmt.variant(variant).map_or(0, |(tag, _)| tag);
+					let payload = self.generate_payload_pointer(&val, tag)?;
+
+					for (i, binding) in bindings.iter().enumerate() {
+						let Some(name) = binding else {
+							continue;
+						};
+
+						let mut bound = self.generate_field_pointer(&payload, &i.to_string())?;
+						self.ensure_rvalue(&mut bound)?;
+						let (symbol, reg) = self.local_symbol_table.create_local(name, &bound.format());
+						self.writer.write_local_alloc(&reg, &bound.format())?;
+						self.writer.write_store(&bound, &LLVMValue::VirtualRegister(reg))?;
+						self.local_symbol_table.insert(symbol);
+					}
+				}
+			}
+			self.generate_block(&arm.block, expected_fmt)?;
+			self.pop_scope()?;
+			self.writer.write_branch(&tail_label)?;
+		}
+
+		if !arms.iter().any(|arm| matches!(arm.pattern, Pattern::Wildcard)) {
+			self.writer.write_label(&unreachable_label)?;
+			self.writer.write_unreachable()?;
+		}
+		self.writer.write_label(&tail_label)?;
+
+		Ok(LLVMValue::None)
+	}
+
+	// Generate break or continue by branching to the innermost loop's tail or condition,
+	// freeing the boxes and vecs of the scopes being left
+	pub fn generate_loop_control(&mut self, keyword: Identifier) -> Result<LLVMValue> {
+		let Some((continue_label, break_label, depth)) = self.loop_labels.last().cloned() else {
+			return Err(Error::LoopControlOutsideLoop { keyword });
+		};
+
+		let owned: Vec<LLVMValue> = self.owners[depth..].concat();
+		self.generate_frees(&owned)?;
+
+		match keyword {
+			Identifier::Continue => self.writer.write_branch(&continue_label)?,
+			_ => self.writer.write_branch(&break_label)?,
+		}
+
+		// Anything following the branch is in a new unnamed block
+		self.update_virtual_register(1);
+
+		Ok(LLVMValue::None)
+	}
+
+	// Add a struct's format to the type registry and define its named type
+	pub fn declare_struct(&mut self, name: &str, fields: &[StructField]) -> Result<()> {
+		let mut field_fmts: Vec<(String, RegisterFormat)> = Vec::new();
+		for field in fields {
+			field_fmts.push((field.name.to_owned(), self.get_format_from_type(&field.field_type)?));
+		}
+
+		let fmt = RegisterFormat::Struct { name: name.to_owned(), fields: field_fmts };
+		self.writer.write_struct_type(&fmt)?;
+		self.types.declare(name, fmt)
+	}
+
+	// Add an enum's format to the type registry and define its named type, along with the payload struct of each variant
+	pub fn declare_enum(&mut self, name: &str, variants: &[EnumVariant]) -> Result<()> {
+		let mut variant_fmts: Vec<(String, Vec<RegisterFormat>)> = Vec::new();
+		for variant in variants {
+			let payload = variant.payload.iter().map(|payload_type| self.get_format_from_type(payload_type)).collect::<Result<Vec<RegisterFormat>>>()?;
+			variant_fmts.push((variant.name.to_owned(), payload));
+		}
+
+		let fmt = RegisterFormat::Enum { name: name.to_owned(), variants: variant_fmts };
+		self.writer.write_enum_type(&fmt)?;
+		self.types.declare(name, fmt)
+	}
+
+	// Add a function's signature to the global symbol table so it can be called before its definition
+	pub fn declare_function(&mut self, name: &str, parameters: &[FunctionParameter], return_type: &Type) -> Result<()> {
+		let return_fmt = self.get_format_from_type(return_type)?;
+		let mut param_fmts: Vec<RegisterFormat> = Vec::new();
+		for param in parameters {
+			param_fmts.push(self.get_format_from_type(&param.param_type)?);
+		}
+
+		let signature = FunctionSignature::new(&param_fmts, return_fmt);
+		let (func_symbol, _func_register) = self.global_symbol_table.create_function(name, &signature);
+		self.global_symbol_table.insert(func_symbol);
+
+		Ok(())
+	}
+
 	// Generate a function, including header and body
-	pub fn generate_function(&mut self, name: String, parameters: &Vec<FunctionParameter>, body_block: &Vec<ASTNode>, return_type: &Type) -> Result<LLVMValue> {
+	pub fn generate_function(&mut self, name: &str, parameters: &[FunctionParameter], body_block: &[ASTNode], return_type: &Type) -> Result<LLVMValue> {
 		let return_fmt = self.get_format_from_type(return_type)?;
 		let mut param_values: Vec<LLVMValue> = Vec::new();
-		for (_, param) in parameters.iter().enumerate() {
-			param_values.push(LLVMValue::VirtualRegister(VirtualRegister::new(param.name.to_owned(), self.get_format_from_type(&param.param_type)?, true)));
+		for param in parameters.iter() {
+			param_values.push(LLVMValue::VirtualRegister(VirtualRegister::new(param.name.to_owned() + ".arg", self.get_format_from_type(&param.param_type)?, true)));
 		}
 
-		let signature = FunctionSignature::new(&param_values.iter().map(|p| p.format()).collect(), return_fmt.clone());
-
 		// Write function header, convert args into locals, generate the block statements, and close function definition
-		self.writer.write_function_header(&name, &param_values, &return_fmt)?;
+		self.writer.write_function_header(name, &param_values, &return_fmt)?;
 
-		for (i, param) in param_values.iter().enumerate() {
-			let arg_reg = VirtualRegister::new("arg.".to_owned() + &i.to_string(), param.format(), true);
-			let (local_symbol, local_reg) = self.local_symbol_table.create_local(&parameters[i].name, &param.format());
+		for (param, param_value) in parameters.iter().zip(param_values.iter()) {
+			let (local_symbol, local_reg) = self.local_symbol_table.create_local(&param.name, &param_value.format());
 
-			self.writer.write_local_alloc(&local_reg, &param.format())?;
-			self.writer.write_store(&LLVMValue::VirtualRegister(arg_reg), &LLVMValue::VirtualRegister(VirtualRegister::new(parameters[i].name.to_owned(), local_symbol.value().format().to_pointer(), true)))?;
+			self.writer.write_local_alloc(&local_reg, &param_value.format())?;
+			self.writer.write_store(param_value, &LLVMValue::VirtualRegister(local_reg))?;
+			self.take_ownership(&local_symbol)?;
 			self.local_symbol_table.insert(local_symbol);
 		}
 
-		// Add to symbol table before parsing body so recursive functions can exist
-		let (func_symbol, _func_register) = self.global_symbol_table.create_function(&name, &signature);
-		self.global_symbol_table.insert(func_symbol);
+		self.generate_block(body_block, &Some(return_fmt.clone()))?;
 
-		for block_statement in body_block {
-			self.ast_to_llvm(block_statement, Some(return_fmt.clone()))?;
+		// Every block needs a terminator; void functions may fall off the end, while analysis
+		// guarantees other functions return before reaching it
+		if let RegisterFormat::Void = return_fmt {
+			let owned = std::mem::take(&mut self.owners).concat();
+			self.generate_frees(&owned)?;
+			self.writer.write_ret(&LLVMValue::None)?;
+		} else {
+			self.writer.write_unreachable()?;
 		}
 
 		self.writer.write_function_close()?;
 		self.free_register_count = 0;
 		self.next_register = 1;
+		self.stack_values = 0;
 		self.local_symbol_table.clear();
+		self.owners = vec![Vec::new()];
 
 		Ok(LLVMValue::None)
 	}
 
 	// Generate a return statement
 	pub fn generate_return(&mut self, expr: &Option<Box<ASTNode>>, expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
-		let mut val = match expr {
-			Some(expr) => self.ast_to_llvm(expr, None)?,
+		let val = match expr {
+			Some(expr) => self.generate_value(expr, expected_fmt.as_ref())?,
 			None => LLVMValue::VirtualRegister(VirtualRegister::new(self.update_virtual_register(0).to_string(), RegisterFormat::Void, true)),
 		};
-		self.ensure_rvalue(&mut val)?;
 		if let Some(fmt) = expected_fmt {
-			fmt.expect(val.format())?;
+			fmt.expect_convertible(val.format())?;
 		}
 
 		if let LLVMValue::None = val {
 			return Err(Error::ExpressionExpected)
 		}
 
+		// Boxes and vecs of every open scope are freed, after any being returned was moved out
+		let owned = self.owners.concat();
+		self.generate_frees(&owned)?;
+
 		self.update_virtual_register(1);
 		self.writer.write_ret(&val)?;
 
@@ -378,26 +1483,28 @@ impl Generator {
 	}
 
 	// Generate a function call given name and args
-	pub fn generate_function_call(&mut self, name: &String, args: &Vec<ASTNode>) -> Result<LLVMValue> {
+	pub fn generate_function_call(&mut self, name: &str, args: &[ASTNode]) -> Result<LLVMValue> {
+		// Get function symbol so integer literal args can take the format of their parameters
+		let func_symbol = self.global_symbol_table.get(name)?.clone();
+		let param_fmts = match func_symbol.value().format() {
+			RegisterFormat::Function { signature } => signature.params().clone(),
+			_ => Vec::new(),
+		};
+
 		// Parse arguments
 		let mut arg_vals: Vec<LLVMValue> = Vec::new();
-		for node in args {
-			arg_vals.push(self.ast_to_llvm(node, None)?);
-		}
-
-		for (_, arg) in arg_vals.iter_mut().enumerate() {
-			self.ensure_rvalue(arg)?;
+		for (i, node) in args.iter().enumerate() {
+			arg_vals.push(self.generate_value(node, param_fmts.get(i))?);
 		}
 
-		// Get function symbol and check that args match
-		let func_symbol = self.global_symbol_table.get(name)?.clone();
+		// Check that args match
 		if let Symbol::Function { name, value } = func_symbol {
 			// existing function call, check arg types
 			if let RegisterFormat::Function { signature } = value.format() {
 				// Guaranteed if symbol is function
 				for (i, fmt) in signature.params().iter().enumerate() {
-					if !fmt.can_convert_to(&arg_vals.get(i).map_or(RegisterFormat::Void, |res| res.format())) {
-						return Err(Error::ArgumentMismatch { expected: signature, received: arg_vals })
+					if !arg_vals.get(i).map_or(RegisterFormat::Void, |res| res.format()).can_convert_to(fmt) {
+						return Err(Error::ArgumentMismatch { expected: signature, received: arg_vals.iter().map(|val| val.format()).collect() })
 					}
 				}
 
@@ -407,31 +1514,76 @@ impl Generator {
 
 				self.writer.write_function_call(&name, &arg_vals, &ret_reg)?;
 
-				return Ok(ret_reg)
+				Ok(ret_reg)
 			} else {
 				// this is just for Rust
-				return Ok(LLVMValue::None)
+				Ok(LLVMValue::None)
 			}
 		} else {
-			return Err(Error::ExpressionExpected)
+			Err(Error::ExpressionExpected)
 		}
 	}
 
 	// Generate print statement
-	pub fn generate_print(&mut self, expr: &ASTNode) -> Result<LLVMValue> {
-		let mut val = self.ast_to_llvm(expr, None)?;
-		self.ensure_rvalue(&mut val)?;
-		
-		if let LLVMValue::None = val {
-			return Err(Error::ExpressionExpected)
+	// Print all values on one line, separated by spaces, using a single printf call
+	pub fn generate_print(&mut self, exprs: &[ASTNode]) -> Result<LLVMValue> {
+		let mut specifiers: Vec<&str> = Vec::new();
+		let mut vals: Vec<LLVMValue> = Vec::new();
+
+		for expr in exprs {
+			let mut val = self.ast_to_llvm(expr, None)?;
+			self.ensure_rvalue(&mut val)?;
+
+			match val.format() {
+				// printf takes 64-bit integers and doubles
+				fmt if fmt.is_signed() => {
+					val = self.generate_format_cast(val, &RegisterFormat::Integer)?;
+					specifiers.push("%lld");
+				},
+				fmt if fmt.is_integer() => {
+					val = self.generate_format_cast(val, &RegisterFormat::UInt64)?;
+					specifiers.push("%llu");
+				},
+				fmt if fmt.is_float() => {
+					val = self.generate_format_cast(val, &RegisterFormat::Float64)?;
+					specifiers.push("%g");
+				},
+				RegisterFormat::String => specifiers.push("%s"),
+				RegisterFormat::Boolean => {
+					val = self.generate_boolean_name(val)?;
+					specifiers.push("%s");
+				},
+				_ => return Err(Error::ExpressionExpected),
+			}
+
+			vals.push(val);
 		}
 
+		let fstring = self.intern_string(&(specifiers.join(" ") + "\n"));
+
 		self.update_virtual_register(1);
-		self.writer.write_print(&val)?;
+		self.writer.write_print(&fstring, &vals)?;
 
 		Ok(LLVMValue::None)
 	}
 
+	// Turn a boolean into the string "true" or "false"
+	pub fn generate_boolean_name(&mut self, val: LLVMValue) -> Result<LLVMValue> {
+		let true_name = self.intern_string("true");
+		let false_name = self.intern_string("false");
+
+		// Known values don't need to be chosen at runtime
+		if let LLVMValue::Constant(Constant::Boolean(b)) = val {
+			return Ok(LLVMValue::Constant(if b { true_name } else { false_name }));
+		}
+
+		let reg = self.update_virtual_register(1);
+		let name = VirtualRegister::new(reg.to_string(), RegisterFormat::String, true);
+		self.writer.write_select(&val, &LLVMValue::Constant(true_name), &LLVMValue::Constant(false_name), &name)?;
+
+		Ok(LLVMValue::VirtualRegister(name))
+	}
+
 	pub fn load_numbered_register(&mut self, format: RegisterFormat, val: LLVMValue) -> Result<LLVMValue> {
 		if let LLVMValue::VirtualRegister(_) = &val {
 			let reg = self.update_virtual_register(1);
@@ -446,25 +1598,25 @@ impl Generator {
 	}
 
 	// Verify that LLVMValues are able to be operated on by arithmetic
-	pub fn ensure_arithmetic_operands(&mut self, mut left: &mut LLVMValue, mut right: &mut LLVMValue) -> Result<()> {
-		self.ensure_rvalue(&mut left)?;
-		self.ensure_rvalue(&mut right)?;
+	pub fn ensure_arithmetic_operands(&mut self, left: &mut LLVMValue, right: &mut LLVMValue) -> Result<()> {
+		self.ensure_rvalue(left)?;
+		self.ensure_rvalue(right)?;
 
-		if let RegisterFormat::Integer = left.format() {
-			if let RegisterFormat::Integer = right.format() {
-				Ok(())
-			} else {
-				Err(Error::InvalidArithmeticOperand { received: right.format() })
-			}
-		} else {
+		if !left.format().is_number() {
 			Err(Error::InvalidArithmeticOperand { received: left.format() })
+		} else if !right.format().is_number() {
+			Err(Error::InvalidArithmeticOperand { received: right.format() })
+		} else if left.format() != right.format() {
+			Err(Error::MismatchedOperands { left: left.format(), right: right.format() })
+		} else {
+			Ok(())
 		}
 	}
 
 	// Verify that left and right can be compared
-	pub fn ensure_comparison_operands(&mut self, mut left: &mut LLVMValue, mut right: &mut LLVMValue, op: &Token) -> Result<()> {
-		self.ensure_rvalue(&mut left)?;
-		self.ensure_rvalue(&mut right)?;
+	pub fn ensure_comparison_operands(&mut self, left: &mut LLVMValue, right: &mut LLVMValue, op: &Token) -> Result<()> {
+		self.ensure_rvalue(left)?;
+		self.ensure_rvalue(right)?;
 
 		let left_fmt = left.format();
 		let right_fmt = right.format();
@@ -479,7 +1631,7 @@ impl Generator {
 	// Ensure that given LLVM Values are in operatable form
 	pub fn ensure_rvalue(&mut self, node: &mut LLVMValue) -> Result<()> {
 		match node {
-			LLVMValue::None => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::Constant(Constant::Integer(3)), received: node.clone() }),
+			LLVMValue::None => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::Constant(Constant::Integer { value: 3, format: RegisterFormat::Integer }), received: node.clone() }),
 			LLVMValue::VirtualRegister(r) => {
 				match r.format() {
 					RegisterFormat::Identifier { id_type } => {
@@ -502,16 +1654,6 @@ impl Generator {
 	}
 
 	pub fn get_format_from_type(&mut self, source: &Type) -> Result<RegisterFormat> {
-		let fmt = match source {
-			Type::Named { type_name } => {
-				TYPE_FORMATS.iter().find_map(|type_fmt| if type_name == type_fmt.0 { Some(type_fmt.1.clone()) } else { None }  )
-			},
-			Type::Void => Some(RegisterFormat::Void),
-		};
-
-		match fmt {
-			Some(type_format) => Ok(type_format),
-			None => Err(Error::TypeUnknown { received: source.to_owned() }),
-		}
+		self.types.format_from_type(source)
 	}
 }
\ No newline at end of file
@@ -2,14 +2,41 @@ use std::fs::File;
 use std::io::Write;
 
 use crate::error::*;
-use crate::generating::llvm::LLVMValue;
+use crate::generating::llvm::{Constant, LLVMValue};
 
-use super::{Constant, FunctionSignature, Label, RegisterFormat, VirtualRegister};
+use super::{Label, RegisterFormat, VirtualRegister};
+
+// Functions the generated code declares or defines next to the program's own, so functions and globals can't take their names
+pub const RUNTIME_SYMBOLS: &[&str] = &[
+	"printf", "malloc", "free", "realloc", "fflush", "write", "dprintf", "abort",
+	"arithmetic_trap", "index_trap", "pop_trap", "alloc_trap",
+];
+
+// Names and messages of the runtime checks emitted with checked arithmetic
+pub const ARITHMETIC_TRAPS: &[(&str, &str)] = &[
+	("divide_by_zero", "attempt to divide by zero"),
+	("divide_overflow", "attempt to divide with overflow"),
+	("remainder_by_zero", "attempt to calculate the remainder with a divisor of zero"),
+	("remainder_overflow", "attempt to calculate the remainder with overflow"),
+];
+
+// Message of the runtime check emitted with checked indexing, given the length and then the index
+pub const INDEX_TRAP: &str = "index out of bounds: the len is %lld but the index is %lld";
+
+// Message of the runtime check every vec's pop helper makes, which is emitted whenever vec methods are used
+pub const POP_TRAP: &str = "attempt to pop from an empty vec";
+
+// Message of the runtime check made after every heap allocation, which is emitted whenever boxes are made or vecs pushed to
+pub const ALLOC_TRAP: &str = "memory allocation failed";
 
 #[derive(Debug)]
 pub struct Writer {
 	filename: String,
 	target: File,
+	// Body of the function being written, which is held back until its close so the allocas can be put first
+	body: Option<String>,
+	// Every alloca of the function being written, which go in its entry block so loops reuse their stack slots
+	allocas: String,
 }
 
 impl Writer {
@@ -17,13 +44,14 @@ impl Writer {
 		Self {
 			filename,
 			target,
+			body: None,
+			allocas: String::new(),
 		}
 	}
 
 	pub fn from_filename(filename: String) -> std::io::Result<Self> {
 		File::create(&filename)
-			.map(|file| Ok(Self::new(filename.clone(), file)))
-			.map_err(|cause| cause)?
+			.map(|file| Self::new(filename.clone(), file))
 	}
 
 	pub fn write_preamble(&mut self) -> Result<()> {
@@ -33,8 +61,6 @@ source_filename = \"{0}\"
 target datalayout = \"e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128\"
 target triple = \"x86_64-pc-linux-gnu\"
 
-@print_int_fstring = private unnamed_addr constant [4 x i8] c\"%d\\0A\\00\", align 1
-
 ", self.filename
 		))?;
 
@@ -43,26 +69,62 @@ target triple = \"x86_64-pc-linux-gnu\"
 
 	pub fn write_postamble(&mut self) -> Result<()> {
 		self.write(
-&format!("declare i32 @printf(i8*, ...) #1
+"declare i32 @printf(i8*, ...) #1
+declare i8* @malloc(i64) #1
+declare void @free(i8*) #1
+declare i8* @realloc(i8*, i64) #1
+
+attributes #0 = { noinline nounwind optnone uwtable \"frame-pointer\"=\"all\" \"min-legal-vector-width\"=\"0\" \"no-trapping-math\"=\"true\" \"stack-protector-buffer-size\"=\"8\" \"target-cpu\"=\"x86-64\" \"target-features\"=\"+cx8,+fxsr,+mmx,+sse,+sse2,+x87\" \"tune-cpu\"=\"generic\" }
+attributes #1 = { \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"stack-protector-buffer-size\"=\"8\" \"target-cpu\"=\"x86-64\" \"target-features\"=\"+cx8,+fxsr,+mmx,+sse,+sse2,+x87\" \"tune-cpu\"=\"generic\" }
+
+!llvm.module.flags = !{!0, !1, !2, !3, !4}
+!llvm.ident = !{!5}
+
+!0 = !{i32 1, !\"wchar_size\", i32 4}
+!1 = !{i32 7, !\"PIC Level\", i32 2}
+!2 = !{i32 7, !\"PIE Level\", i32 2}
+!3 = !{i32 7, !\"uwtable\", i32 1}
+!4 = !{i32 7, !\"frame-pointer\", i32 2}
+!5 = !{!\"ICD compiler\"}"
+		)
+	}
 
-attributes #0 = {{ noinline nounwind optnone uwtable \"frame-pointer\"=\"all\" \"min-legal-vector-width\"=\"0\" \"no-trapping-math\"=\"true\" \"stack-protector-buffer-size\"=\"8\" \"target-cpu\"=\"x86-64\" \"target-features\"=\"+cx8,+fxsr,+mmx,+sse,+sse2,+x87\" \"tune-cpu\"=\"generic\" }}
-attributes #1 = {{ \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"stack-protector-buffer-size\"=\"8\" \"target-cpu\"=\"x86-64\" \"target-features\"=\"+cx8,+fxsr,+mmx,+sse,+sse2,+x87\" \"tune-cpu\"=\"generic\" }}
+	// Define the named type of a struct, made of the types of its fields in order
+	pub fn write_struct_type(&mut self, format: &RegisterFormat) -> Result<()> {
+		let RegisterFormat::Struct { fields, .. } = format else {
+			return Err(Error::StructExpected { received: format.clone() });
+		};
 
-!llvm.module.flags = !{{!0, !1, !2, !3, !4}}
-!llvm.ident = !{{!5}}
+		let field_types: Vec<String> = fields.iter().map(|field| field.1.format_type()).collect();
+		self.writeln(&format!("{} = type {{ {} }}", format.format_type(), field_types.join(", ")))
+	}
 
-!0 = !{{i32 1, !\"wchar_size\", i32 4}}
-!1 = !{{i32 7, !\"PIC Level\", i32 2}}
-!2 = !{{i32 7, !\"PIE Level\", i32 2}}
-!3 = !{{i32 7, !\"uwtable\", i32 1}}
-!4 = !{{i32 7, !\"frame-pointer\", i32 2}}
-!5 = !{{!\"ICD compiler\"}}")
-		)
+	// Define the named type of an enum, i.e. its tag followed by words enough for any payload, after the payload
+	// struct of each variant that holds values
+	pub fn write_enum_type(&mut self, format: &RegisterFormat) -> Result<()> {
+		let RegisterFormat::Enum { variants, .. } = format else {
+			return Err(Error::EnumExpected { received: format.clone() });
+		};
+
+		for (i, variant) in variants.iter().enumerate() {
+			if let (false, Some(payload_fmt)) = (variant.1.is_empty(), format.payload_format(i)) {
+				self.write_struct_type(&payload_fmt)?;
+			}
+		}
+
+		self.writeln(&format!("{} = type {{ i32, [{} x i64] }}", format.format_type(), format.payload_words()))
+	}
+
+	// Define a global variable with its initial value
+	pub fn write_global(&mut self, register: &VirtualRegister, value: &Constant) -> Result<()> {
+		self.writeln(&format!("{register} = dso_local global {} {value}", value.const_type()))
 	}
 
 	// Allocate space for local variable
 	pub fn write_local_alloc(&mut self, register: &VirtualRegister, format: &RegisterFormat) -> Result<()> {
-		self.writeln(&format!("\t{register} = alloca {}", format.format_type()))
+		self.allocas += &format!("\t{register} = alloca {}\n", format.format_type());
+
+		Ok(())
 	}
 
 	// Load src register into target
@@ -75,77 +137,132 @@ attributes #1 = {{ \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"sta
 		self.writeln(&format!("\tstore {} {src}, {} {trg}", src.val_type(), trg.val_type()))
 	}
 
-	// Write a multiplication to the LLVM file
-	pub fn write_mul(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
-		let l_val: String = match left {
-			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
-		}?;
+	// Get the text of an operand used in an instruction
+	fn operand(val: &LLVMValue) -> Result<String> {
+		match val {
+			LLVMValue::VirtualRegister(v) => Ok(v.to_string()),
+			LLVMValue::Constant(c) => Ok(c.to_string()),
+			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: val.clone() })
+		}
+	}
 
-		let r_val: String = match right {
-			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
-		}?;
+	// Write a binary arithmetic instruction, e.g. 'add nsw', to the LLVM file
+	pub fn write_arithmetic(&mut self, instruction: &str, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		let l_val = Self::operand(left)?;
+		let r_val = Self::operand(right)?;
 
-		self.writeln(&format!("\t%{reg} = mul nsw {} {l_val}, {r_val}", left.val_type()))
+		self.writeln(&format!("\t%{reg} = {instruction} {} {l_val}, {r_val}", left.val_type()))
 	}
 
-	// Write a subtraction operation to the LLVM file
-	pub fn write_sub(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
-		let l_val: String = match left {
-			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
-		}?;
+	// Pick the signed, unsigned or float variant of an instruction based on the format of its operands
+	fn number_instruction<'a>(val: &LLVMValue, signed: &'a str, unsigned: &'a str, float: &'a str) -> &'a str {
+		match val.format() {
+			fmt if fmt.is_float() => float,
+			fmt if fmt.is_signed() => signed,
+			_ => unsigned,
+		}
+	}
 
-		let r_val: String = match right {
-			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
-		}?;
+	// Write a multiplication to the LLVM file
+	pub fn write_mul(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		self.write_arithmetic(Self::number_instruction(left, "mul nsw", "mul nuw", "fmul"), left, right, reg)
+	}
 
-		self.writeln(&format!("\t%{reg} = sub nsw {} {l_val}, {r_val}", left.val_type()))
+	// Write a subtraction operation to the LLVM file
+	pub fn write_sub(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		self.write_arithmetic(Self::number_instruction(left, "sub nsw", "sub nuw", "fsub"), left, right, reg)
 	}
 
 	// Write an addition operation to the LLVM file
 	pub fn write_add(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
-		let l_val: String = match left {
-			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
-		}?;
+		self.write_arithmetic(Self::number_instruction(left, "add nsw", "add nuw", "fadd"), left, right, reg)
+	}
 
-		let r_val: String = match right {
-			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
-		}?;
+	// Write a division operation to the LLVM file
+	pub fn write_div(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		self.write_arithmetic(Self::number_instruction(left, "sdiv", "udiv", "fdiv"), left, right, reg)
+	}
 
-		self.writeln(&format!("\t%{reg} = add nsw {} {l_val}, {r_val}", left.val_type()))
+	// Write a remainder operation to the LLVM file
+	pub fn write_rem(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		self.write_arithmetic(Self::number_instruction(left, "srem", "urem", "frem"), left, right, reg)
 	}
 
-	// Write an addition operation to the LLVM file
-	pub fn write_div(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
-		let l_val: String = match left {
-			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
-		}?;
+	// Write a bitwise and to the LLVM file
+	pub fn write_and(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		self.write_arithmetic("and", left, right, reg)
+	}
+
+	// Write a negation (0 - val, or fneg for floats) to the LLVM file
+	pub fn write_neg(&mut self, val: &LLVMValue, reg: u32) -> Result<()> {
+		if val.format().is_float() {
+			self.writeln(&format!("\t%{reg} = fneg {} {}", val.val_type(), Self::operand(val)?))
+		} else {
+			self.writeln(&format!("\t%{reg} = sub nsw {} 0, {}", val.val_type(), Self::operand(val)?))
+		}
+	}
+
+	// Write a logical not (val xor true) to the LLVM file
+	pub fn write_not(&mut self, val: &LLVMValue, reg: u32) -> Result<()> {
+		self.writeln(&format!("\t%{reg} = xor {} {}, true", val.val_type(), Self::operand(val)?))
+	}
+
+	// Get a pointer to the element at index of the array that array points to
+	pub fn write_element_pointer(&mut self, array: &LLVMValue, index: &LLVMValue, reg: &VirtualRegister) -> Result<()> {
+		let array_type = array.format().rvalue_format().format_type();
+
+		self.writeln(&format!("\t{reg} = getelementptr inbounds {array_type}, {} {}, i64 0, {} {}", array.val_type(), Self::operand(array)?, index.val_type(), Self::operand(index)?))
+	}
 
-		let r_val: String = match right {
-			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
-		}?;
+	// Get a pointer to the field at index of the struct that strct points to
+	pub fn write_field_pointer(&mut self, strct: &LLVMValue, index: usize, reg: &VirtualRegister) -> Result<()> {
+		let struct_type = strct.format().rvalue_format().format_type();
 
-		self.writeln(&format!("\t%{reg} = udiv {} {l_val}, {r_val}", left.val_type()))
+		self.writeln(&format!("\t{reg} = getelementptr inbounds {struct_type}, {} {}, i32 0, i32 {index}", strct.val_type(), Self::operand(strct)?))
 	}
 
-	// Compare left and right via 'op'
+	// Get a pointer to the element at index of the elements that base points to the first of
+	pub fn write_offset_pointer(&mut self, base: &LLVMValue, index: &LLVMValue, reg: &VirtualRegister) -> Result<()> {
+		let element_type = reg.format().rvalue_format().format_type();
+
+		self.writeln(&format!("\t{reg} = getelementptr inbounds {element_type}, {} {}, {} {}", base.val_type(), Self::operand(base)?, index.val_type(), Self::operand(index)?))
+	}
+
+	// Set the element at index of a tuple to element, giving the new tuple
+	pub fn write_insert_value(&mut self, tuple: &LLVMValue, element: &LLVMValue, index: usize, reg: &VirtualRegister) -> Result<()> {
+		self.writeln(&format!("\t{reg} = insertvalue {} {}, {} {}, {index}", tuple.val_type(), Self::operand(tuple)?, element.val_type(), Self::operand(element)?))
+	}
+
+	// Get the element at index of a tuple
+	pub fn write_extract_value(&mut self, tuple: &LLVMValue, index: usize, reg: &VirtualRegister) -> Result<()> {
+		self.writeln(&format!("\t{reg} = extractvalue {} {}, {index}", tuple.val_type(), Self::operand(tuple)?))
+	}
+
+	// Convert val to the format of reg with a cast instruction, e.g. 'sext'
+	pub fn write_cast(&mut self, instruction: &str, val: &LLVMValue, reg: &VirtualRegister) -> Result<()> {
+		self.writeln(&format!("\t{reg} = {instruction} {} {} to {}", val.val_type(), Self::operand(val)?, reg.reg_type()))
+	}
+
+	// Compare left and right via 'op', with fcmp for floats and icmp otherwise
 	pub fn write_cmp(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32, op: String) -> Result<()> {
-		self.writeln(&format!("\t%{reg} = icmp {op} {} {left}, {right}", left.val_type()))
+		let instruction = if left.format().is_float() { "fcmp" } else { "icmp" };
+
+		self.writeln(&format!("\t%{reg} = {instruction} {op} {} {left}, {right}", left.val_type()))
+	}
+
+	// Write a select choosing between two values based on a condition
+	pub fn write_select(&mut self, cond: &LLVMValue, if_true: &LLVMValue, if_false: &LLVMValue, reg: &VirtualRegister) -> Result<()> {
+		self.writeln(&format!("\t{reg} = select i1 {}, {} {}, {} {}", Self::operand(cond)?, if_true.val_type(), Self::operand(if_true)?, if_false.val_type(), Self::operand(if_false)?))
+	}
+
+	// Write a phi node choosing a value based on which label control came from
+	pub fn write_phi(&mut self, format: &RegisterFormat, incoming: &[(LLVMValue, &Label)], reg: u32) -> Result<()> {
+		let mut arms: Vec<String> = Vec::new();
+		for (val, label) in incoming {
+			arms.push(format!("[ {}, %{label} ]", Self::operand(val)?));
+		}
+
+		self.writeln(&format!("\t%{reg} = phi {} {}", format.format_type(), arms.join(", ")))
 	}
 
 	// Write given label to output
@@ -158,30 +275,47 @@ attributes #1 = {{ \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"sta
 		self.writeln(&format!("\tbr {cond_type} {condition}, label %{t_label}, label %{f_label}", cond_type=condition.val_type()))
 	}
 
+	// Branch to the label of the case equal to val, or to default_label if there's none
+	pub fn write_switch(&mut self, val: &LLVMValue, default_label: &Label, cases: &[(LLVMValue, &Label)]) -> Result<()> {
+		self.writeln(&format!("\tswitch {} {}, label %{default_label} [", val.val_type(), Self::operand(val)?))?;
+		for (case, label) in cases {
+			self.writeln(&format!("\t\t{} {}, label %{label}", case.val_type(), Self::operand(case)?))?;
+		}
+
+		self.writeln("\t]")
+	}
+
 	// Write a direct branch to a label
 	pub fn write_branch(&mut self, label: &Label) -> Result<()> {
 		self.writeln(&format!("\tbr label %{label}"))
 	}
 
 	// Write function header
-	pub fn write_function_header(&mut self, name: &str, param_values: &Vec<LLVMValue>, return_fmt: &RegisterFormat) -> Result<()> {
+	pub fn write_function_header(&mut self, name: &str, param_values: &[LLVMValue], return_fmt: &RegisterFormat) -> Result<()> {
 		self.write(&format!("define dso_local {return_type} @{name}(", return_type=return_fmt.format_type()))?;
 
 		for (i, param) in param_values.iter().enumerate() {
-			self.write(&format!("{param_type} %arg.{i}{comma}", param_type=param.val_type(), comma={if i < param_values.len() - 1 { "," } else { "" }}))?;
+			self.write(&format!("{param_type} {param}{comma}", param_type=param.val_type(), comma={if i < param_values.len() - 1 { "," } else { "" }}))?;
 		}
 
-		self.writeln(") #0 {")
+		self.writeln(") #0 {")?;
+		self.body = Some(String::new());
+
+		Ok(())
 	}
 
-	// Write function close
+	// Write function close, putting the function's allocas ahead of its body
 	pub fn write_function_close(&mut self) -> Result<()> {
+		let body = self.body.take().unwrap_or_default();
+		let allocas = std::mem::take(&mut self.allocas);
+		self.write(&allocas)?;
+		self.write(&body)?;
 		self.writeln("}")?;
 		self.writeln("")
 	}
 
 	// Write function call and put res in trg;
-	pub fn write_function_call(&mut self, name: &String, arg_vals: &Vec<LLVMValue>, ret_reg: &LLVMValue) -> Result<()> {
+	pub fn write_function_call(&mut self, name: &str, arg_vals: &[LLVMValue], ret_reg: &LLVMValue) -> Result<()> {
 		self.write("\t")?;
 		if let RegisterFormat::Void = ret_reg.format() {} else {
 			self.write(&format!("{ret_reg} = "))?;
@@ -207,21 +341,259 @@ attributes #1 = {{ \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"sta
 		}
 	}
 
-	// Print integer (i32)
-	pub fn write_print(&mut self, val: &LLVMValue) -> Result<()> {
-		self.writeln(&format!("\tcall i32(i8*, ...) @printf(i8* getelementptr inbounds ([4 x i8], [4 x i8]* @print_int_fstring, i32 0, i32 0), {} {val})", val.val_type()))
+	// Mark the end of a block that can't be reached
+	pub fn write_unreachable(&mut self) -> Result<()> {
+		self.writeln("\tunreachable")
+	}
+
+	// Size in bytes of a value of format as a constant expression, which LLVM works out from the target's layout
+	pub fn size_of(format: &RegisterFormat) -> String {
+		let format_type = format.format_type();
+		format!("ptrtoint ({format_type}* getelementptr ({format_type}, {format_type}* null, i32 1) to i64)")
+	}
+
+	// Allocate heap space for a value of format, giving its address as an i8*, which is null if allocation failed
+	pub fn write_malloc(&mut self, format: &RegisterFormat, reg: &VirtualRegister) -> Result<()> {
+		self.writeln(&format!("\t{reg} = call i8* @malloc(i64 {})", Self::size_of(format)))
+	}
+
+	// Free heap memory at an address given as an i8*; freeing null does nothing
+	pub fn write_free(&mut self, ptr: &LLVMValue) -> Result<()> {
+		self.writeln(&format!("\tcall void @free(i8* {})", Self::operand(ptr)?))
+	}
+
+	// Call the trap routine with the message for the named trap; control doesn't return
+	// The generator only names traps in ARITHMETIC_TRAPS, so any other name is a bug in the compiler
+	pub fn write_trap(&mut self, name: &str) -> Result<()> {
+		let Some((_, message)) = ARITHMETIC_TRAPS.iter().find(|trap| trap.0 == name) else {
+			unreachable!("no arithmetic trap is named '{name}'");
+		};
+		let len = Self::trap_message(message).len();
+
+		self.writeln(&format!("\tcall void @arithmetic_trap(i8* getelementptr inbounds ([{size} x i8], [{size} x i8]* @trap.{name}, i32 0, i32 0), i64 {len})", size=len + 1))?;
+		self.write_unreachable()
+	}
+
+	// Write the messages and routine used by write_trap, which flushes printed output, reports to stderr and aborts
+	pub fn write_arithmetic_traps(&mut self) -> Result<()> {
+		for (name, message) in ARITHMETIC_TRAPS {
+			let message = Self::trap_message(message);
+			self.writeln(&format!("@trap.{name} = private unnamed_addr constant [{} x i8] c\"{}\\0A\\00\", align 1", message.len() + 1, &message[..message.len() - 1]))?;
+		}
+
+		self.write(
+"
+define private void @arithmetic_trap(i8* %message, i64 %len) noreturn nounwind {
+	%flushed = call i32 @fflush(i8* null)
+	%written = call i64 @write(i32 2, i8* %message, i64 %len)
+	call void @abort()
+	unreachable
+}
+
+")
+	}
+
+	// Call the index trap routine with an out of bounds index and the length of the array; control doesn't return
+	pub fn write_index_trap_call(&mut self, index: &LLVMValue, length: &LLVMValue) -> Result<()> {
+		self.writeln(&format!("\tcall void @index_trap(i64 {}, i64 {})", Self::operand(index)?, Self::operand(length)?))?;
+		self.write_unreachable()
+	}
+
+	// Write the message and routine used by write_index_trap_call, which flushes printed output,
+	// reports the index and length to stderr and aborts
+	pub fn write_index_trap(&mut self) -> Result<()> {
+		let message = Self::trap_message(INDEX_TRAP);
+		let size = message.len() + 1;
+		self.writeln(&format!("@trap.index_out_of_bounds = private unnamed_addr constant [{size} x i8] c\"{}\\0A\\00\", align 1", &message[..message.len() - 1]))?;
+
+		self.write(&format!(
+"
+define private void @index_trap(i64 %index, i64 %len) noreturn nounwind {{
+	%flushed = call i32 @fflush(i8* null)
+	%written = call i32 (i32, i8*, ...) @dprintf(i32 2, i8* getelementptr inbounds ([{size} x i8], [{size} x i8]* @trap.index_out_of_bounds, i32 0, i32 0), i64 %len, i64 %index)
+	call void @abort()
+	unreachable
+}}
+
+"))
+	}
+
+	// Call the pop trap routine; control doesn't return
+	pub fn write_pop_trap_call(&mut self) -> Result<()> {
+		self.writeln("\tcall void @pop_trap()")?;
+		self.write_unreachable()
+	}
+
+	// Call the allocation trap routine; control doesn't return
+	pub fn write_alloc_trap_call(&mut self) -> Result<()> {
+		self.writeln("\tcall void @alloc_trap()")?;
+		self.write_unreachable()
+	}
+
+	// Write the message and routine used by write_pop_trap_call
+	pub fn write_pop_trap(&mut self) -> Result<()> {
+		self.write_message_trap("pop_trap", "pop_empty", POP_TRAP)
+	}
+
+	// Write the message and routine used by write_alloc_trap_call
+	pub fn write_alloc_trap(&mut self) -> Result<()> {
+		self.write_message_trap("alloc_trap", "alloc_failed", ALLOC_TRAP)
+	}
+
+	// Write a message as @trap.<name> and a routine that flushes printed output, reports the message to stderr and aborts
+	fn write_message_trap(&mut self, routine: &str, name: &str, message: &str) -> Result<()> {
+		let message = Self::trap_message(message);
+		let size = message.len() + 1;
+		self.writeln(&format!("@trap.{name} = private unnamed_addr constant [{size} x i8] c\"{}\\0A\\00\", align 1", &message[..message.len() - 1]))?;
+
+		self.write(&format!(
+"
+define private void @{routine}() noreturn nounwind {{
+	%flushed = call i32 @fflush(i8* null)
+	%written = call i64 @write(i32 2, i8* getelementptr inbounds ([{size} x i8], [{size} x i8]* @trap.{name}, i32 0, i32 0), i64 {})
+	call void @abort()
+	unreachable
+}}
+
+", message.len()))
+	}
+
+	// Name of the helper function doing a vec method for vecs of the element format, quoted since it holds any characters
+	// the element format is written with
+	pub fn vec_helper(method: &str, element: &RegisterFormat) -> String {
+		format!("\"vec.{method}.{element}\"")
+	}
+
+	// Define the push and pop helpers of vecs of the element format, which take the address of the vec
+	// Pushing to a full vec reallocates its elements with double the capacity, or 4 if it had none, trapping
+	// if that fails, and popping from an empty vec traps
+	pub fn write_vec_helpers(&mut self, element: &RegisterFormat) -> Result<()> {
+		let vec_type = RegisterFormat::Vec { element: Box::new(element.clone()) }.format_type();
+		let element_type = element.format_type();
+
+		self.write(&format!(
+"define private void @{push}({vec_type}* %vec, {element_type} %value) {{
+entry:
+	%data.ptr = getelementptr inbounds {vec_type}, {vec_type}* %vec, i32 0, i32 0
+	%data = load {element_type}*, {element_type}** %data.ptr
+	%len.ptr = getelementptr inbounds {vec_type}, {vec_type}* %vec, i32 0, i32 1
+	%len = load i64, i64* %len.ptr
+	%cap.ptr = getelementptr inbounds {vec_type}, {vec_type}* %vec, i32 0, i32 2
+	%cap = load i64, i64* %cap.ptr
+	%full = icmp eq i64 %len, %cap
+	br i1 %full, label %grow, label %store
+
+grow:
+	%empty = icmp eq i64 %cap, 0
+	%doubled = mul i64 %cap, 2
+	%new.cap = select i1 %empty, i64 4, i64 %doubled
+	%bytes = mul i64 %new.cap, {size}
+	%old = bitcast {element_type}* %data to i8*
+	%grown = call i8* @realloc(i8* %old, i64 %bytes)
+	%failed = icmp eq i8* %grown, null
+	br i1 %failed, label %trap, label %allocated
+
+allocated:
+	%new.data = bitcast i8* %grown to {element_type}*
+	store {element_type}* %new.data, {element_type}** %data.ptr
+	store i64 %new.cap, i64* %cap.ptr
+	br label %store
+
+store:
+	%slots = phi {element_type}* [ %data, %entry ], [ %new.data, %allocated ]
+	%slot = getelementptr inbounds {element_type}, {element_type}* %slots, i64 %len
+	store {element_type} %value, {element_type}* %slot
+	%new.len = add i64 %len, 1
+	store i64 %new.len, i64* %len.ptr
+	ret void
+
+trap:
+", push=Self::vec_helper("push", element), size=Self::size_of(element)))?;
+		self.write_alloc_trap_call()?;
+
+		self.write(&format!(
+"}}
+
+define private {element_type} @{pop}({vec_type}* %vec) {{
+entry:
+	%len.ptr = getelementptr inbounds {vec_type}, {vec_type}* %vec, i32 0, i32 1
+	%len = load i64, i64* %len.ptr
+	%empty = icmp eq i64 %len, 0
+	br i1 %empty, label %trap, label %pop
+
+trap:
+", pop=Self::vec_helper("pop", element)))?;
+		self.write_pop_trap_call()?;
+
+		self.write(&format!(
+"
+pop:
+	%new.len = sub i64 %len, 1
+	store i64 %new.len, i64* %len.ptr
+	%data.ptr = getelementptr inbounds {vec_type}, {vec_type}* %vec, i32 0, i32 0
+	%data = load {element_type}*, {element_type}** %data.ptr
+	%slot = getelementptr inbounds {element_type}, {element_type}* %data, i64 %new.len
+	%value = load {element_type}, {element_type}* %slot
+	ret {element_type} %value
+}}
+
+"))
+	}
+
+	// Declare the functions used by the trap routines
+	pub fn write_trap_declarations(&mut self) -> Result<()> {
+		self.write(
+"declare i32 @fflush(i8*)
+declare i64 @write(i32, i8*, i64)
+declare i32 @dprintf(i32, i8*, ...)
+declare void @abort() noreturn
+
+")
+	}
+
+	// Full text printed by a trap, including the trailing newline
+	fn trap_message(message: &str) -> String {
+		format!("error: {message}\n")
+	}
+
+	// Call printf with the given format string and values
+	pub fn write_print(&mut self, fstring: &Constant, vals: &[LLVMValue]) -> Result<()> {
+		let mut args = vec![format!("{} {fstring}", fstring.const_type())];
+		for val in vals {
+			args.push(format!("{} {}", val.val_type(), Self::operand(val)?));
+		}
+
+		self.writeln(&format!("\tcall i32(i8*, ...) @printf({})", args.join(", ")))
+	}
+
+	// Define a private global holding the characters of a string, followed by a null terminator
+	pub fn write_string_constant(&mut self, id: &str, text: &str) -> Result<()> {
+		let mut encoded = String::new();
+		for byte in text.bytes() {
+			// Quotes, backslashes and unprintable bytes have to be written as hex escapes
+			if (byte.is_ascii_graphic() || byte == b' ') && byte != b'"' && byte != b'\\' {
+				encoded.push(byte as char);
+			} else {
+				encoded.push_str(&format!("\\{byte:02X}"));
+			}
+		}
+
+		self.writeln(&format!("@{id} = private unnamed_addr constant [{} x i8] c\"{encoded}\\00\", align 1", text.len() + 1))
 	}
 
 	pub fn write(&mut self, msg: &str) -> Result<()> {
+		if let Some(body) = &mut self.body {
+			body.push_str(msg);
+			return Ok(());
+		}
+
 		self.target.write(msg.as_bytes())
 			.map(|_| Ok(()))
 			.map_err(|cause| Error::FileWriteError { cause })?
 	}
 
 	pub fn writeln(&mut self, msg: &str) -> Result<()> {
-		self.target.write((msg.to_owned() + "\n").as_bytes())
-			.map(|_| Ok(()))
-			.map_err(|cause| Error::FileWriteError { cause })?
+		self.write(&(msg.to_owned() + "\n"))
 	}
 
 }
\ No newline at end of file
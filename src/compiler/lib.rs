@@ -1,5 +1,9 @@
+// Errors carry enough context to render diagnostics, so they are large by design
+#![allow(clippy::result_large_err)]
+
 pub mod cli;
 pub mod scanning;
 pub mod parsing;
+pub mod analyzing;
 pub mod generating;
 pub mod error;
\ No newline at end of file
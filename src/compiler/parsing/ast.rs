@@ -1,3 +1,4 @@
+use crate::scanning::span::Span;
 use crate::scanning::token::{Token, Literal};
 
 #[derive(Debug, Clone)]
@@ -5,6 +6,23 @@ pub enum Type {
 	Named {
 		type_name: String
 	},
+	Array {
+		element: Box<Type>,
+		length: usize,
+	},
+	Tuple {
+		elements: Vec<Type>,
+	},
+	Reference {
+		referent: Box<Type>,
+		mutable: bool,
+	},
+	Box {
+		inner: Box<Type>,
+	},
+	Vec {
+		element: Box<Type>,
+	},
 	Void
 }
 
@@ -12,6 +30,15 @@ impl std::fmt::Display for Type {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Type::Named { type_name } => write!(f, "{type_name}"),
+			Type::Array { element, length } => write!(f, "[{element}; {length}]"),
+			Type::Tuple { elements } => {
+				let elements: Vec<String> = elements.iter().map(|element| element.to_string()).collect();
+				write!(f, "({})", elements.join(", "))
+			},
+			Type::Reference { referent, mutable: true } => write!(f, "&mut {referent}"),
+			Type::Reference { referent, mutable: false } => write!(f, "&{referent}"),
+			Type::Box { inner } => write!(f, "Box<{inner}>"),
+			Type::Vec { element } => write!(f, "Vec<{element}>"),
 			Type::Void => write!(f, "void"),
 		}
 	}
@@ -21,20 +48,126 @@ impl std::fmt::Display for Type {
 pub struct FunctionParameter {
 	pub name: String,
 	pub param_type: Type,
+	pub span: Span,
 }
 
 #[derive(Debug, Clone)]
-pub enum ASTNode {
+pub struct StructField {
+	pub name: String,
+	pub field_type: Type,
+	pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+	pub name: String,
+	pub payload: Vec<Type>,
+	pub span: Span,
+}
+
+// Value an arm of a match statement is chosen for
+#[derive(Debug, Clone)]
+pub enum Pattern {
+	// '<enum>::<variant>(<binding>, ...)', binding each value of the payload to a name, or ignoring it with '_'
+	Variant {
+		enum_name: String,
+		variant: String,
+		bindings: Vec<Option<String>>,
+	},
+	Integer(i128),
+	Wildcard,
+}
+
+impl Pattern {
+	// Whether every value other matches is also matched by self
+	pub fn covers(&self, other: &Pattern) -> bool {
+		match (self, other) {
+			(Pattern::Wildcard, _) => true,
+			(Pattern::Variant { enum_name, variant, .. }, Pattern::Variant { enum_name: other_enum, variant: other_variant, .. }) => enum_name == other_enum && variant == other_variant,
+			(Pattern::Integer(x), Pattern::Integer(y)) => x == y,
+			_ => false,
+		}
+	}
+}
+
+impl std::fmt::Display for Pattern {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Pattern::Variant { enum_name, variant, bindings } => {
+				write!(f, "{enum_name}::{variant}")?;
+				if bindings.is_empty() {
+					return Ok(());
+				}
+
+				let names: Vec<&str> = bindings.iter().map(|binding| binding.as_deref().unwrap_or("_")).collect();
+				write!(f, "({})", names.join(", "))
+			},
+			Pattern::Integer(x) => write!(f, "{x}"),
+			Pattern::Wildcard => write!(f, "_"),
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+	pub pattern: Pattern,
+	pub block: Vec<ASTNode>,
+	// Region of source the pattern was parsed from
+	pub span: Span,
+}
+
+// Node of the AST along with the region of source it was parsed from
+#[derive(Debug, Clone)]
+pub struct ASTNode {
+	pub kind: NodeKind,
+	pub span: Span,
+}
+
+impl ASTNode {
+	pub fn new(kind: NodeKind, span: Span) -> Self {
+		Self {
+			kind,
+			span,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeKind {
 	Literal(Literal),
 	Binary { token: Token, left: Box<ASTNode>, right: Box<ASTNode> },
+	Unary { token: Token, expr: Box<ASTNode> },
+	Cast { expr: Box<ASTNode>, cast_type: Type },
+	Reference { expr: Box<ASTNode>, mutable: bool },
+	Deref { expr: Box<ASTNode> },
+	BoxNew { value: Box<ASTNode> },
+	VecNew,
+	ArrayLiteral { elements: Vec<ASTNode> },
+	TupleLiteral { elements: Vec<ASTNode> },
+	Index { array: Box<ASTNode>, index: Box<ASTNode> },
+	StructLiteral { name: String, fields: Vec<(String, ASTNode)> },
+	FieldAccess { expr: Box<ASTNode>, field: String },
+	MethodCall { expr: Box<ASTNode>, method: String, args: Vec<ASTNode> },
+	VariantLiteral { enum_name: String, variant: String, values: Vec<ASTNode> },
 	Print {
-		expr: Box<ASTNode>,
+		exprs: Vec<ASTNode>,
 	},
 	Let { 
 		name: String,
 		val_type: Option<Type>,
 		value: Option<Box<ASTNode>>,
 	},
+	// 'let (<name>, ...) = <value>;', where an element bound to '_' is ignored
+	LetTuple {
+		names: Vec<Option<String>>,
+		val_type: Option<Type>,
+		value: Box<ASTNode>,
+	},
+	Const {
+		name: String,
+		const_type: Type,
+		value: Box<ASTNode>,
+	},
 	If {
 		expr: Box<ASTNode>,
 		block: Vec<ASTNode>,
@@ -44,6 +177,26 @@ pub enum ASTNode {
 		expr: Box<ASTNode>,
 		block: Vec<ASTNode>
 	},
+	For {
+		name: String,
+		start: Box<ASTNode>,
+		end: Box<ASTNode>,
+		inclusive: bool,
+		step: Option<Box<ASTNode>>,
+		block: Vec<ASTNode>,
+	},
+	// 'for <name> in <vec> { ... }', going over the elements of a vec
+	ForEach {
+		name: String,
+		vec: Box<ASTNode>,
+		block: Vec<ASTNode>,
+	},
+	Match {
+		expr: Box<ASTNode>,
+		arms: Vec<MatchArm>,
+	},
+	Break,
+	Continue,
 	FunctionDefinition {
 		name: String,
 		parameters: Vec<FunctionParameter>,
@@ -54,7 +207,21 @@ pub enum ASTNode {
 		name: String,
 		args: Vec<ASTNode>,
 	},
+	StructDefinition {
+		name: String,
+		fields: Vec<StructField>,
+	},
+	EnumDefinition {
+		name: String,
+		variants: Vec<EnumVariant>,
+	},
 	Return {
 		return_val: Option<Box<ASTNode>>,
 	}
+}
+
+// Whole source file, i.e. every global statement in the order it was defined
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+	pub statements: Vec<ASTNode>,
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 use crate::error::*;
+use crate::error::diagnostic::Diagnostics;
 use crate::scanning::Scanner;
+use crate::scanning::span::Span;
 use crate::scanning::token::*;
 use ast::*;
 
@@ -8,6 +10,7 @@ pub mod ast;
 pub const OPERATOR_PRECEDENCE: &[(Token, u8)] = &[
 	(Token::Slash, 12),
 	(Token::Asterisk, 12),
+	(Token::Percent, 12),
 	(Token::Plus, 11),
 	(Token::Minus, 11),
 	(Token::GreaterThan, 9),
@@ -16,6 +19,8 @@ pub const OPERATOR_PRECEDENCE: &[(Token, u8)] = &[
 	(Token::LessThanEqual, 9),
 	(Token::Equals2, 8),
 	(Token::ExclamationEqual, 8),
+	(Token::Ampersand2, 5),
+	(Token::Pipe2, 4),
 	(Token::Equals, 1),
 ];
 
@@ -23,6 +28,11 @@ pub const OPERATOR_PRECEDENCE: &[(Token, u8)] = &[
 pub struct Parser {
 	scanner: Scanner,
 	current_token: Option<Token>,
+	current_span: Span,
+	previous_span: Span,
+	diagnostics: Diagnostics,
+	// Whether a '{' after a name starts a struct literal, rather than the block of an if, while or for statement
+	struct_literals_allowed: bool,
 }
 
 impl Parser {
@@ -30,6 +40,10 @@ impl Parser {
 		let mut parser = Self {
 			scanner,
 			current_token: None,
+			current_span: Span::default(),
+			previous_span: Span::default(),
+			diagnostics: Diagnostics::new(),
+			struct_literals_allowed: true,
 		};
 
 		parser.scan_next()?;
@@ -40,37 +54,118 @@ impl Parser {
 	pub fn scan_next(&mut self) -> Result<()> {
 		let token = self.scanner.scan()?;
 
-		self.current_token = token;
+		match token {
+			Some(SpannedToken { token, span }) => {
+				self.previous_span = std::mem::replace(&mut self.current_span, span);
+				self.current_token = Some(token);
+			},
+			None => {
+				self.current_token = None;
+			},
+		}
+
 		Ok(())
 	}
 
+	// Errors recorded while recovering from invalid syntax
+	pub fn diagnostics(&self) -> &Diagnostics {
+		&self.diagnostics
+	}
+
+	pub fn take_diagnostics(&mut self) -> Diagnostics {
+		std::mem::take(&mut self.diagnostics)
+	}
+
+	// Skip current token during error recovery; scanning errors are recorded rather than returned
+	fn skip_token(&mut self) {
+		if let Err(error) = self.scan_next() {
+			// Unknown tokens are consumed by the scanner, anything else means no more input can be read
+			if !matches!(error.inner(), Error::UnknownToken { .. }) {
+				self.current_token = Some(Token::EndOfFile);
+			}
+
+			self.diagnostics.push(error);
+		}
+	}
+
+	// Skip to the end of the current statement, i.e. past its ';' or any block it opened,
+	// stopping early at a '}' closing the enclosing block, an 'fn' keyword or EOF
+	pub fn synchronize_statement(&mut self) {
+		let mut depth = 0;
+
+		loop {
+			match &self.current_token {
+				Some(Token::Semicolon) if depth == 0 => {
+					self.skip_token();
+					return;
+				},
+				Some(Token::LeftCurly) => depth += 1,
+				Some(Token::RightCurly) => {
+					if depth == 0 {
+						return;
+					}
+
+					depth -= 1;
+					if depth == 0 {
+						self.skip_token();
+						return;
+					}
+				},
+				Some(Token::Literal(Literal::Identifier(Identifier::Function))) | Some(Token::EndOfFile) | None => return,
+				_ => {},
+			}
+
+			self.skip_token();
+		}
+	}
+
+	// Skip to the start of the next function definition, or of the next global, constant, struct or
+	// enum declaration outside of any block, or EOF
+	pub fn synchronize_global(&mut self) {
+		let mut depth = 0;
+
+		loop {
+			match &self.current_token {
+				Some(Token::Literal(Literal::Identifier(Identifier::Function))) | Some(Token::EndOfFile) | None => return,
+				Some(Token::Literal(Literal::Identifier(Identifier::Let | Identifier::Const | Identifier::Struct | Identifier::Enum))) if depth == 0 => return,
+				Some(Token::LeftCurly) => depth += 1,
+				Some(Token::RightCurly) => depth = 0.max(depth - 1),
+				_ => {},
+			}
+
+			self.skip_token();
+		}
+	}
+
+	// Span of the token currently being looked at
+	pub fn current_span(&self) -> &Span {
+		&self.current_span
+	}
+
+	// Create a span from start through the last token consumed
+	pub fn span_from(&self, start: &Span) -> Span {
+		start.to(&self.previous_span)
+	}
+
 	// Verify that token matches what is expected
 	pub fn match_token(&mut self, tokens: &[Token]) -> Result<Token> {
-		for (_, token) in tokens.iter().enumerate() {
-			let matched = match self.current_token.clone() {
-				Some(t) => *token == t,
-				None => false,
-			};
-
-			if matched {
-				return Ok(self.current_token.clone().unwrap());
+		if let Some(t) = &self.current_token {
+			if tokens.contains(t) {
+				return Ok(t.clone());
 			}
 		}
 
 		// Error handling
-		let t: Token;
-		if let Some(tok) = self.current_token.clone() { t = tok; } else {t = Token::None};
-		Err(Error::InvalidToken { expected: tokens.to_vec(), received: t })
+		let received = self.current_token.clone().unwrap_or(Token::None);
+		Err(Error::InvalidToken { expected: tokens.to_vec(), received }.with_span(&self.current_span))
 	}
 
 	// Verify that current token matches an identifier and return said identifier
 	pub fn match_identifier(&mut self) -> Result<Identifier> {
 		match self.current_token.clone() {
-			Some(t) => match t {
-				Token::Literal(Literal::Identifier(i)) => Ok(i),
-				_ => {Err(Error::IdentifierExpected { received: t })},
-			},
-			None => Err(Error::IdentifierExpected { received: Token::None })
+			Some(Token::Literal(Literal::Identifier(i))) => Ok(i),
+			Some(t) => Err(Error::IdentifierExpected { received: t }.with_span(&self.current_span)),
+			None => Err(Error::IdentifierExpected { received: Token::None }.with_span(&self.current_span)),
 		}
 	}
 
@@ -78,17 +173,226 @@ impl Parser {
 	pub fn expect_identifier(&mut self, identifier: Identifier) -> Result<()> {
 		let matched_identifier = self.match_identifier()?;
 
-		if std::mem::discriminant(&matched_identifier) == std::mem::discriminant(&identifier) { Ok(()) } else { Err(Error::InvalidIdentifier { expected: [identifier].to_vec(), received: matched_identifier })}
+		if std::mem::discriminant(&matched_identifier) == std::mem::discriminant(&identifier) {
+			Ok(())
+		} else {
+			Err(Error::InvalidIdentifier { expected: [identifier].to_vec(), received: matched_identifier }.with_span(&self.current_span))
+		}
+	}
+
+	// Parse a type name following a ':' or '->', an array type '[<type>; <length>]', a tuple type '(<type>, ...)'
+	// a reference type '&<type>' or '&mut <type>', a box type 'Box<<type>>' or a vec type 'Vec<<type>>'
+	pub fn parse_type(&mut self) -> Result<Type> {
+		if let Ok(owner @ (Identifier::Box | Identifier::Vec)) = self.match_identifier() {
+			self.scan_next()?;
+			self.match_token(&[Token::LessThan])?;
+			self.scan_next()?;
+			let inner = Box::new(self.parse_type()?);
+			self.match_token(&[Token::GreaterThan])?;
+			self.scan_next()?;
+
+			return Ok(match owner {
+				Identifier::Box => Type::Box { inner },
+				_ => Type::Vec { element: inner },
+			});
+		}
+
+		if self.match_token(&[Token::Ampersand]).is_ok() {
+			self.scan_next()?;
+			let mutable = self.expect_identifier(Identifier::Mut).is_ok();
+			if mutable {
+				self.scan_next()?;
+			}
+
+			return Ok(Type::Reference { referent: Box::new(self.parse_type()?), mutable });
+		}
+
+		// A single type in parentheses is just that type
+		if self.match_token(&[Token::LeftParen]).is_ok() {
+			self.scan_next()?;
+			let mut elements = vec![self.parse_type()?];
+			while self.match_token(&[Token::Comma]).is_ok() {
+				self.scan_next()?;
+				elements.push(self.parse_type()?);
+			}
+			self.match_token(&[Token::RightParen])?;
+			self.scan_next()?;
+
+			return Ok(if elements.len() == 1 { elements.remove(0) } else { Type::Tuple { elements } });
+		}
+
+		if self.match_token(&[Token::LeftBracket]).is_ok() {
+			self.scan_next()?;
+			let element = Box::new(self.parse_type()?);
+			self.match_token(&[Token::Semicolon])?;
+			self.scan_next()?;
+
+			// Length must be an integer literal
+			let Some(Token::Literal(Literal::Integer(length))) = self.current_token.clone() else {
+				return Err(Error::LiteralExpected { received: self.current_token.clone().unwrap_or(Token::None) }.with_span(&self.current_span));
+			};
+			self.scan_next()?;
+			self.match_token(&[Token::RightBracket])?;
+			self.scan_next()?;
+
+			return Ok(Type::Array { element, length: length as usize });
+		}
+
+		let type_id = self.match_identifier()?;
+
+		match type_id {
+			Identifier::Symbol(t) => {
+				self.scan_next()?;
+				Ok(Type::Named { type_name: t })
+			},
+			_ => Err(Error::TypeExpected { received: type_id }.with_span(&self.current_span)),
+		}
+	}
+
+	// Parse every global statement in the file; syntax errors are recorded in diagnostics and
+	// parsing resumes at the next global statement
+	pub fn parse_program(&mut self) -> Program {
+		let mut statements = Vec::new();
+
+		loop {
+			match self.parse_global_statement() {
+				Ok(Some(statement)) => statements.push(statement),
+				Ok(None) => break,
+				Err(error) => {
+					self.diagnostics.push(error);
+					self.synchronize_global();
+				},
+			}
+		}
+
+		Program { statements }
 	}
 
-	// Parse a global statement (function for now)
+	// Parse a function definition, global variable, constant, struct or enum declaration
 	pub fn parse_global_statement(&mut self) -> Result<Option<ASTNode>> {
 		if self.match_token(&[Token::EndOfFile]).is_ok() {
 			return Ok(None);
 		}
 
-		// Should follow 'fn <name>(<param 1>, <param 2>, ...) { <body_block> }
-		self.expect_identifier(Identifier::Function)?;
+		let start = self.current_span.clone();
+		let kind = match self.match_identifier()? {
+			Identifier::Function => self.parse_function_definition()?,
+			// Global variables are declared just like locals
+			Identifier::Let => return self.parse_statement(),
+			Identifier::Const => self.parse_const_statement()?,
+			Identifier::Struct => self.parse_struct_definition()?,
+			Identifier::Enum => self.parse_enum_definition()?,
+			id => return Err(Error::InvalidIdentifier { expected: [Identifier::Function, Identifier::Let, Identifier::Const, Identifier::Struct, Identifier::Enum].to_vec(), received: id }.with_span(&self.current_span)),
+		};
+
+		Ok(Some(ASTNode::new(kind, self.span_from(&start))))
+	}
+
+	// Parse 'const <symbol>: <type> = <expr>;'
+	pub fn parse_const_statement(&mut self) -> Result<NodeKind> {
+		self.scan_next()?;
+		let name = match self.match_identifier()? {
+			Identifier::Symbol(symbol) => symbol,
+			id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+		};
+		self.scan_next()?;
+
+		// Type is required
+		self.match_token(&[Token::Colon])?;
+		self.scan_next()?;
+		let const_type = self.parse_type()?;
+
+		self.match_token(&[Token::Equals])?;
+		self.scan_next()?;
+		let value = Box::new(self.parse_binary_operation(0)?);
+		self.match_token(&[Token::Semicolon])?;
+		self.scan_next()?;
+
+		Ok(NodeKind::Const { name, const_type, value })
+	}
+
+	// Parse 'struct <name> { <field>: <type>, ... }'; a trailing comma is allowed
+	pub fn parse_struct_definition(&mut self) -> Result<NodeKind> {
+		self.scan_next()?;
+		let name = match self.match_identifier()? {
+			Identifier::Symbol(symbol) => symbol,
+			id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+		};
+		self.scan_next()?;
+		self.match_token(&[Token::LeftCurly])?;
+		self.scan_next()?;
+
+		let mut fields: Vec<StructField> = Vec::new();
+		while self.match_token(&[Token::RightCurly]).is_err() {
+			let field_start = self.current_span.clone();
+			let field_name = match self.match_identifier()? {
+				Identifier::Symbol(symbol) => symbol,
+				id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+			};
+			self.scan_next()?;
+
+			// Field type is required
+			self.match_token(&[Token::Colon])?;
+			self.scan_next()?;
+			let field_type = self.parse_type()?;
+			fields.push(StructField { name: field_name, field_type, span: self.span_from(&field_start) });
+
+			if self.match_token(&[Token::RightCurly]).is_err() {
+				self.match_token(&[Token::Comma])?;
+				self.scan_next()?;
+			}
+		}
+		self.scan_next()?;
+
+		Ok(NodeKind::StructDefinition { name, fields })
+	}
+
+	// Parse 'enum <name> { <variant>, <variant>(<type>, ...), ... }'; a trailing comma is allowed
+	pub fn parse_enum_definition(&mut self) -> Result<NodeKind> {
+		self.scan_next()?;
+		let name = match self.match_identifier()? {
+			Identifier::Symbol(symbol) => symbol,
+			id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+		};
+		self.scan_next()?;
+		self.match_token(&[Token::LeftCurly])?;
+		self.scan_next()?;
+
+		let mut variants: Vec<EnumVariant> = Vec::new();
+		while self.match_token(&[Token::RightCurly]).is_err() {
+			let variant_start = self.current_span.clone();
+			let variant_name = match self.match_identifier()? {
+				Identifier::Symbol(symbol) => symbol,
+				id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+			};
+			self.scan_next()?;
+
+			// Payload types are separated by commas; don't allow trailing comma
+			let mut payload: Vec<Type> = Vec::new();
+			if self.match_token(&[Token::LeftParen]).is_ok() {
+				self.scan_next()?;
+				payload.push(self.parse_type()?);
+				while self.match_token(&[Token::Comma]).is_ok() {
+					self.scan_next()?;
+					payload.push(self.parse_type()?);
+				}
+				self.match_token(&[Token::RightParen])?;
+				self.scan_next()?;
+			}
+			variants.push(EnumVariant { name: variant_name, payload, span: self.span_from(&variant_start) });
+
+			if self.match_token(&[Token::RightCurly]).is_err() {
+				self.match_token(&[Token::Comma])?;
+				self.scan_next()?;
+			}
+		}
+		self.scan_next()?;
+
+		Ok(NodeKind::EnumDefinition { name, variants })
+	}
+
+	// Parse 'fn <name>(<param 1>, <param 2>, ...) { <body_block> }'
+	pub fn parse_function_definition(&mut self) -> Result<NodeKind> {
 		self.scan_next()?;
 
 		self.expect_identifier(Identifier::Symbol("".to_string()))?;
@@ -105,28 +409,24 @@ impl Parser {
 
 		// Parse parameters until right parenthesis is met; don't allow trailing comma
 		while self.expect_identifier(Identifier::Symbol("".to_string())).is_ok() {
+			let param_start = self.current_span.clone();
 			let id = self.match_identifier()?;
 			self.scan_next()?;
 
 			// Param type is required
 			self.match_token(&[Token::Colon])?;
 			self.scan_next()?;
-			let type_id = self.match_identifier()?;
-			self.scan_next()?;
+			let param_type = self.parse_type()?;
+			let span = self.span_from(&param_start);
 
-			let param_type = match type_id {
-				Identifier::Symbol(t) => Type::Named { type_name: t},
-				_ => Err(Error::TypeExpected { received: type_id })?,
-			};
-
-			if !self.match_token(&[Token::RightParen]).is_ok() {
+			if self.match_token(&[Token::RightParen]).is_err() {
 				self.match_token(&[Token::Comma])?;
 				self.scan_next()?;
 			}
 
 			// Add id to parameter list (guaranteed to run)
 			if let Identifier::Symbol(s) = id {
-				param_list.push(FunctionParameter { name: s, param_type });
+				param_list.push(FunctionParameter { name: s, param_type, span });
 			}
 		}
 
@@ -135,22 +435,16 @@ impl Parser {
 		self.scan_next()?;
 
 		// Return type should be specified; if not, classify it as return void
-		let return_type: Type;
-		if self.match_token(&[Token::Arrow]).is_ok() {
+		let return_type = if self.match_token(&[Token::Arrow]).is_ok() {
 			self.scan_next()?;
-			let type_id = self.match_identifier()?;
-			self.scan_next()?;
-			return_type = match type_id {
-				Identifier::Symbol(t) => Type::Named { type_name: t },
-				_ => Err(Error::TypeExpected { received: type_id })?,
-			};
+			self.parse_type()?
 		} else {
-			return_type = Type::Void;
-		}
+			Type::Void
+		};
 
 		let body_block: Vec<ASTNode> = self.parse_block_statement()?;
 
-		Ok(Some(ASTNode::FunctionDefinition { name, parameters: param_list, body_block, return_type }))
+		Ok(NodeKind::FunctionDefinition { name, parameters: param_list, body_block, return_type })
 	}
 
 	// Parse a statement, which for now contains an identifier followed by a binary expression followed by a semicolon
@@ -161,23 +455,43 @@ impl Parser {
 		}
 
 		// Statement should follow the pattern "<identifier> <binary_expr> ;"
+		let start = self.current_span.clone();
+
+		// Assignments through a reference start with '*' or '(' rather than a name, e.g. '*p = 1;' or '(*p).x = 1;'
+		if self.match_token(&[Token::Asterisk, Token::LeftParen]).is_ok() {
+			let kind = self.parse_expression_statement()?;
+			return Ok(Some(ASTNode::new(kind, self.span_from(&start))));
+		}
+
 		let identifier = self.match_identifier()?;
 
-		Ok(Some(match &identifier {
+		let kind = match &identifier {
 			Identifier::Print => {
 				self.scan_next()?;
-				Ok(ASTNode::Print {
-					expr: {
-						let b = Box::new(self.parse_binary_operation(0)?);
+				Ok(NodeKind::Print {
+					exprs: {
+						// Values are separated by commas; don't allow trailing comma
+						let mut exprs = vec![self.parse_binary_operation(0)?];
+						while self.match_token(&[Token::Comma]).is_ok() {
+							self.scan_next()?;
+							exprs.push(self.parse_binary_operation(0)?);
+						}
 						self.match_token(&[Token::Semicolon])?;
 						self.scan_next()?;
 
-						b
+						exprs
 					}
 				})
 			},
 			Identifier::Let => {
 				self.scan_next()?;
+
+				// 'let (<symbol>, ...) = <value>;' destructures a tuple
+				if self.match_token(&[Token::LeftParen]).is_ok() {
+					let kind = self.parse_let_tuple()?;
+					return Ok(Some(ASTNode::new(kind, self.span_from(&start))));
+				}
+
 				// Let should be formatted as either 'let <symbol> = <value>;' or 'let <symbol>;'
 				let id = self.match_identifier()?;
 				self.scan_next()?;
@@ -193,19 +507,14 @@ impl Parser {
 								let val = Some(Box::new(self.parse_binary_operation(0)?));
 								self.match_token(&[Token::Semicolon])?;
 								self.scan_next()?;
-								Ok(ASTNode::Let {
+								Ok(NodeKind::Let {
 									name: symbol,
 									val_type: None,
 									value: val
 								})
 							},
 							Token::Colon => {
-								let type_id = self.match_identifier()?;
-								self.scan_next()?;
-								let val_type = Some(match type_id {
-									Identifier::Symbol(t) => Type::Named { type_name: t },
-									_ => Err(Error::TypeExpected { received: type_id })?,
-								});
+								let val_type = Some(self.parse_type()?);
 								let semi_or_eq = self.match_token(&[Token::Semicolon, Token::Equals])?;
 								self.scan_next()?;
 
@@ -215,71 +524,297 @@ impl Parser {
 										self.match_token(&[Token::Semicolon])?;
 										self.scan_next()?;
 
-										Ok(ASTNode::Let {
+										Ok(NodeKind::Let {
 											name: symbol,
 											val_type,
 											value: val,
 										})
 									},
 									_ => {
-										Ok(ASTNode::Let { name: symbol, val_type, value: None })
+										Ok(NodeKind::Let { name: symbol, val_type, value: None })
 									}
 								}
 							}
-							_ => Ok(ASTNode::Let { name: symbol, val_type: None, value: None })
+							_ => Ok(NodeKind::Let { name: symbol, val_type: None, value: None })
 						}
 					},
-					_ => Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id })
+					_ => Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.previous_span))
 				}
 			},
-			Identifier::If => {
-				self.scan_next()?;
-				// Follows 'if <expr> <block>'
-				// Should get a boolean expression after if;
-				let expr = Box::new(self.parse_binary_operation(0)?);
-
-				// Parse a block statement and error if there isn't one
-				let block = self.parse_block_statement()?;
-				let is_else = self.expect_identifier(Identifier::Else).is_ok();
-				if is_else {
-					self.scan_next()?;
-				}
-
-				let else_block: Option<Vec<ASTNode>> = if is_else { Some(self.parse_block_statement()?) } else { None };
-
-				Ok(ASTNode::If { expr, block, else_block })
-			},
+			Identifier::If => self.parse_if_statement(),
 			Identifier::While => {
 				self.scan_next()?;
 				// Follows 'while <expr> <block>'
 				// Expecting boolean expression after keyword
-				let expr = Box::new(self.parse_binary_operation(0)?);
+				let expr = Box::new(self.parse_header_expression()?);
 
 				// Parse a block statement and error if there isn't one
 				let block = self.parse_block_statement()?;
 
-				Ok(ASTNode::While { expr, block })
+				Ok(NodeKind::While { expr, block })
+			},
+			Identifier::For => self.parse_for_statement(),
+			Identifier::Match => self.parse_match_statement(),
+			Identifier::Break | Identifier::Continue => {
+				self.scan_next()?;
+				self.match_token(&[Token::Semicolon])?;
+				self.scan_next()?;
+
+				if let Identifier::Break = identifier { Ok(NodeKind::Break) } else { Ok(NodeKind::Continue) }
 			},
 			Identifier::Return => {
 				self.scan_next()?;
 				if self.match_token(&[Token::Semicolon]).is_ok() {
 					self.scan_next()?;
-					Ok(ASTNode::Return { return_val: None})
+					Ok(NodeKind::Return { return_val: None})
 				} else {
-					let return_val = Some(Box::new(self.parse_binary_operation(0)?));
+					// Returning several values separated by commas returns a tuple of them
+					let mut values = vec![self.parse_binary_operation(0)?];
+					while self.match_token(&[Token::Comma]).is_ok() {
+						self.scan_next()?;
+						values.push(self.parse_binary_operation(0)?);
+					}
+					self.match_token(&[Token::Semicolon])?;
 					self.scan_next()?;
 
-					Ok(ASTNode::Return { return_val })
+					let return_val = if values.len() == 1 {
+						values.remove(0)
+					} else {
+						let span = values[0].span.to(&values[values.len() - 1].span);
+						ASTNode::new(NodeKind::TupleLiteral { elements: values }, span)
+					};
+
+					Ok(NodeKind::Return { return_val: Some(Box::new(return_val)) })
 				}
 			},
-			Identifier::Symbol(_) => {
-				let result = self.parse_binary_operation(0)?;
+			Identifier::Symbol(_) => self.parse_expression_statement(),
+			_ => Err(Error::InvalidIdentifier { received: identifier, expected: [Identifier::If, Identifier::Print, Identifier::Let, Identifier::Symbol("".to_string())].to_vec() }.with_span(&self.current_span)),
+		}?;
+
+		Ok(Some(ASTNode::new(kind, self.span_from(&start))))
+	}
+
+	// Parse an expression used as a statement, e.g. an assignment or function call, followed by a ';'
+	pub fn parse_expression_statement(&mut self) -> Result<NodeKind> {
+		let result = self.parse_binary_operation(0)?;
+		self.match_token(&[Token::Semicolon])?;
+		self.scan_next()?;
+
+		Ok(result.kind)
+	}
+
+	// Parse '(<name>, ...) = <expr>;' following 'let', where a type may follow the parentheses after a ':'
+	pub fn parse_let_tuple(&mut self) -> Result<NodeKind> {
+		let names = self.parse_bindings()?;
+
+		let val_type = if self.match_token(&[Token::Colon]).is_ok() {
+			self.scan_next()?;
+			Some(self.parse_type()?)
+		} else {
+			None
+		};
+
+		self.match_token(&[Token::Equals])?;
+		self.scan_next()?;
+		let value = Box::new(self.parse_binary_operation(0)?);
+		self.match_token(&[Token::Semicolon])?;
+		self.scan_next()?;
+
+		Ok(NodeKind::LetTuple { names, val_type, value })
+	}
+
+	// Parse '(<binding>, ...)', where each binding is a name or '_'; don't allow trailing comma
+	pub fn parse_bindings(&mut self) -> Result<Vec<Option<String>>> {
+		self.match_token(&[Token::LeftParen])?;
+
+		let mut bindings: Vec<Option<String>> = Vec::new();
+		loop {
+			self.scan_next()?;
+			if self.match_token(&[Token::Underscore]).is_ok() {
+				bindings.push(None);
+			} else {
+				bindings.push(match self.match_identifier()? {
+					Identifier::Symbol(symbol) => Some(symbol),
+					id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+				});
+			}
+			self.scan_next()?;
+
+			if self.match_token(&[Token::Comma, Token::RightParen])? == Token::RightParen {
+				break;
+			}
+		}
+		self.scan_next()?;
+
+		Ok(bindings)
+	}
+
+	// Parse 'if <expr> <block>' with an optional 'else <block>' or 'else if ...'
+	// An else if becomes an else block holding only the nested if statement
+	pub fn parse_if_statement(&mut self) -> Result<NodeKind> {
+		self.scan_next()?;
+		// Should get a boolean expression after if
+		let expr = Box::new(self.parse_header_expression()?);
+
+		// Parse a block statement and error if there isn't one
+		let block = self.parse_block_statement()?;
+		let is_else = self.expect_identifier(Identifier::Else).is_ok();
+		if !is_else {
+			return Ok(NodeKind::If { expr, block, else_block: None });
+		}
+		self.scan_next()?;
+
+		let else_block = if self.expect_identifier(Identifier::If).is_ok() {
+			let start = self.current_span.clone();
+			let else_if = self.parse_if_statement()?;
+			vec![ASTNode::new(else_if, self.span_from(&start))]
+		} else {
+			self.parse_block_statement()?
+		};
+
+		Ok(NodeKind::If { expr, block, else_block: Some(else_block) })
+	}
+
+	// Parse 'for <symbol> in <expr>..<expr> <block>', where the range may be '..=' to include its end
+	// and may be followed by 'step <expr>', or 'for <symbol> in <expr> <block>' going over the elements of a vec
+	pub fn parse_for_statement(&mut self) -> Result<NodeKind> {
+		self.scan_next()?;
+		let name = match self.match_identifier()? {
+			Identifier::Symbol(symbol) => symbol,
+			id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+		};
+		self.scan_next()?;
+		self.expect_identifier(Identifier::In)?;
+		self.scan_next()?;
+
+		let start = Box::new(self.parse_header_expression()?);
+		let Ok(range) = self.match_token(&[Token::DotDot, Token::DotDotEqual]) else {
+			let block = self.parse_block_statement()?;
+			return Ok(NodeKind::ForEach { name, vec: start, block });
+		};
+		let inclusive = range == Token::DotDotEqual;
+		self.scan_next()?;
+		let end = Box::new(self.parse_header_expression()?);
+
+		// 'step' is only a keyword here, so it can still name variables and functions elsewhere
+		let step = if matches!(self.match_identifier(), Ok(Identifier::Symbol(word)) if word == "step") {
+			self.scan_next()?;
+			Some(Box::new(self.parse_header_expression()?))
+		} else {
+			None
+		};
+
+		let block = self.parse_block_statement()?;
+
+		Ok(NodeKind::For { name, start, end, inclusive, step, block })
+	}
+
+	// Parse 'match <expr> { <pattern> => <block> ... }', where arms may be separated by commas
+	pub fn parse_match_statement(&mut self) -> Result<NodeKind> {
+		self.scan_next()?;
+		let expr = Box::new(self.parse_header_expression()?);
+		self.match_token(&[Token::LeftCurly])?;
+		self.scan_next()?;
+
+		let mut arms: Vec<MatchArm> = Vec::new();
+		while self.match_token(&[Token::RightCurly]).is_err() {
+			let start = self.current_span.clone();
+			let pattern = self.parse_pattern()?;
+			let span = self.span_from(&start);
+
+			self.match_token(&[Token::FatArrow])?;
+			self.scan_next()?;
+			let block = self.parse_block_statement()?;
+			arms.push(MatchArm { pattern, block, span });
+
+			if self.match_token(&[Token::Comma]).is_ok() {
 				self.scan_next()?;
+			}
+		}
+		self.scan_next()?;
 
-				Ok(result)
-			},
-			_ => Err(Error::InvalidIdentifier { received: identifier, expected: [Identifier::If, Identifier::Print, Identifier::Let, Identifier::Symbol("".to_string())].to_vec() }),
-		}?))
+		Ok(NodeKind::Match { expr, arms })
+	}
+
+	// Parse the pattern of a match arm, which is '_', an integer literal, or '<enum>::<variant>' followed by
+	// '(<binding>, ...)' if the variant has a payload, where each binding is a name or '_'
+	pub fn parse_pattern(&mut self) -> Result<Pattern> {
+		if self.match_token(&[Token::Underscore]).is_ok() {
+			self.scan_next()?;
+			return Ok(Pattern::Wildcard);
+		}
+
+		// Integer patterns may be negated
+		let negative = self.match_token(&[Token::Minus]).is_ok();
+		if negative {
+			self.scan_next()?;
+		}
+
+		if let Some(Token::Literal(Literal::Integer(value))) = self.current_token.clone() {
+			self.scan_next()?;
+			return Ok(Pattern::Integer(if negative { -(value as i128) } else { value as i128 }));
+		} else if negative {
+			return Err(Error::LiteralExpected { received: self.current_token.clone().unwrap_or(Token::None) }.with_span(&self.current_span));
+		}
+
+		let enum_name = match self.match_identifier()? {
+			Identifier::Symbol(symbol) => symbol,
+			id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+		};
+		self.scan_next()?;
+		self.match_token(&[Token::Colon2])?;
+		self.scan_next()?;
+		let variant = match self.match_identifier()? {
+			Identifier::Symbol(symbol) => symbol,
+			id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+		};
+		self.scan_next()?;
+
+		let bindings = if self.match_token(&[Token::LeftParen]).is_ok() {
+			self.parse_bindings()?
+		} else {
+			Vec::new()
+		};
+
+		Ok(Pattern::Variant { enum_name, variant, bindings })
+	}
+
+	// Parse an expression followed by the block of an if, while or for statement, so a '{' after a name
+	// starts the block rather than a struct literal
+	pub fn parse_header_expression(&mut self) -> Result<ASTNode> {
+		self.parse_with_struct_literals(false, |parser| parser.parse_binary_operation(0))
+	}
+
+	// Run parse with struct literals allowed or not, restoring whether they were allowed afterwards
+	pub fn parse_with_struct_literals<T>(&mut self, allowed: bool, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+		let previous = std::mem::replace(&mut self.struct_literals_allowed, allowed);
+		let result = parse(self);
+		self.struct_literals_allowed = previous;
+
+		result
+	}
+
+	// Parse '<field>: <expr>, ...}' of a struct literal; a trailing comma is allowed
+	pub fn parse_struct_literal_fields(&mut self) -> Result<Vec<(String, ASTNode)>> {
+		let mut fields: Vec<(String, ASTNode)> = Vec::new();
+		while self.match_token(&[Token::RightCurly]).is_err() {
+			let name = match self.match_identifier()? {
+				Identifier::Symbol(symbol) => symbol,
+				id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+			};
+			self.scan_next()?;
+			self.match_token(&[Token::Colon])?;
+			self.scan_next()?;
+			fields.push((name, self.parse_binary_operation(0)?));
+
+			if self.match_token(&[Token::RightCurly]).is_err() {
+				self.match_token(&[Token::Comma])?;
+				self.scan_next()?;
+			}
+		}
+		self.scan_next()?;
+
+		Ok(fields)
 	}
 
 	// Parse args given to a function call
@@ -299,65 +834,231 @@ impl Parser {
 		Ok(arg_list)
 	}
 
-	// Parse a terminal node, i.e. a node is created with a literal token
+	// Parse a terminal node, i.e. a node is created with a literal token, followed by any indexes into it
 	pub fn parse_terminal_node(&mut self) -> Result<ASTNode> {
 		let Some(token) = self.current_token.clone() else {
-			return Err(Error::LiteralExpected { received: Token::None });
+			return Err(Error::LiteralExpected { received: Token::None }.with_span(&self.current_span));
 		};
+		let start = self.current_span.clone();
 
-		match token {
+		let expr = match token {
 			Token::LeftParen => {
 				self.scan_next()?;
-				let res = self.parse_binary_operation(0)?;
-				self.scan_next()?; 
-				Ok(res)
+				// Expressions separated by commas make a tuple; don't allow trailing comma
+				let mut elements = self.parse_with_struct_literals(true, |parser| {
+					let mut elements = vec![parser.parse_binary_operation(0)?];
+					while parser.match_token(&[Token::Comma]).is_ok() {
+						parser.scan_next()?;
+						elements.push(parser.parse_binary_operation(0)?);
+					}
+
+					Ok(elements)
+				})?;
+				self.match_token(&[Token::RightParen])?;
+				self.scan_next()?;
+
+				if elements.len() == 1 {
+					Ok(ASTNode::new(elements.remove(0).kind, self.span_from(&start)))
+				} else {
+					Ok(ASTNode::new(NodeKind::TupleLiteral { elements }, self.span_from(&start)))
+				}
+			},
+			// Unary operators bind tighter than any binary operator, so only take a terminal as operand
+			_ if token.is_unary() => {
+				self.scan_next()?;
+				let expr = self.parse_terminal_node()?;
+
+				Ok(ASTNode::new(NodeKind::Unary { token, expr: Box::new(expr) }, self.span_from(&start)))
+			},
+			// References and dereferences bind as tightly as unary operators
+			Token::Ampersand => {
+				self.scan_next()?;
+				let mutable = self.expect_identifier(Identifier::Mut).is_ok();
+				if mutable {
+					self.scan_next()?;
+				}
+				let expr = self.parse_terminal_node()?;
+
+				Ok(ASTNode::new(NodeKind::Reference { expr: Box::new(expr), mutable }, self.span_from(&start)))
+			},
+			Token::Asterisk => {
+				self.scan_next()?;
+				let expr = self.parse_terminal_node()?;
+
+				Ok(ASTNode::new(NodeKind::Deref { expr: Box::new(expr) }, self.span_from(&start)))
+			},
+			// 'Box::new(<expr>)' moves a value onto the heap
+			Token::Literal(Literal::Identifier(Identifier::Box)) => {
+				self.parse_constructor()?;
+				let value = self.parse_with_struct_literals(true, |parser| parser.parse_binary_operation(0))?;
+				self.match_token(&[Token::RightParen])?;
+				self.scan_next()?;
+
+				Ok(ASTNode::new(NodeKind::BoxNew { value: Box::new(value) }, self.span_from(&start)))
+			},
+			// 'Vec::new()' makes an empty vec, whose element type is that of the variable it's assigned to
+			Token::Literal(Literal::Identifier(Identifier::Vec)) => {
+				self.parse_constructor()?;
+				self.match_token(&[Token::RightParen])?;
+				self.scan_next()?;
+
+				Ok(ASTNode::new(NodeKind::VecNew, self.span_from(&start)))
+			},
+			Token::Literal(Literal::Integer(x)) => {
+				self.scan_next()?;
+				Ok(ASTNode::new(NodeKind::Literal(Literal::Integer(x)), start.clone()))
+			},
+			Token::Literal(Literal::Float(x)) => {
+				self.scan_next()?;
+				Ok(ASTNode::new(NodeKind::Literal(Literal::Float(x)), start.clone()))
+			},
+			Token::LeftBracket => {
+				self.scan_next()?;
+				// Elements are separated by commas; don't allow trailing comma
+				let mut elements = vec![self.parse_binary_operation(0)?];
+				while self.match_token(&[Token::Comma]).is_ok() {
+					self.scan_next()?;
+					elements.push(self.parse_binary_operation(0)?);
+				}
+				self.match_token(&[Token::RightBracket])?;
+				self.scan_next()?;
+
+				Ok(ASTNode::new(NodeKind::ArrayLiteral { elements }, self.span_from(&start)))
+			},
+			Token::Literal(Literal::String(s)) => {
+				self.scan_next()?;
+				Ok(ASTNode::new(NodeKind::Literal(Literal::String(s)), start.clone()))
 			},
-			Token::Literal(Literal::Integer(x)) => {self.scan_next()?; Ok(ASTNode::Literal(Literal::Integer(x)))},
 			Token::Literal(Literal::Identifier(Identifier::Symbol(c))) => {
 				self.scan_next()?;
 
-				// If a left parentheses follows, parse a function call
+				// If a left parentheses follows, parse a function call, if a left curly follows, a struct literal,
+				// and if '::' follows, a variant of an enum
 				if self.match_token(&[Token::LeftParen]).is_ok() {
 					self.scan_next()?;
-					let arg_list = self.parse_function_args()?;
+					let arg_list = self.parse_with_struct_literals(true, |parser| parser.parse_function_args())?;
+
+					Ok(ASTNode::new(NodeKind::FunctionCall { name: c, args: arg_list }, self.span_from(&start)))
+				} else if self.struct_literals_allowed && self.match_token(&[Token::LeftCurly]).is_ok() {
+					self.scan_next()?;
+					let fields = self.parse_struct_literal_fields()?;
+
+					Ok(ASTNode::new(NodeKind::StructLiteral { name: c, fields }, self.span_from(&start)))
+				} else if self.match_token(&[Token::Colon2]).is_ok() {
+					self.scan_next()?;
+					let variant = match self.match_identifier()? {
+						Identifier::Symbol(symbol) => symbol,
+						id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+					};
+					self.scan_next()?;
 
-					Ok(ASTNode::FunctionCall { name: c, args: arg_list })
+					// Variants without a payload aren't followed by parentheses
+					let values = if self.match_token(&[Token::LeftParen]).is_ok() {
+						self.scan_next()?;
+						self.parse_with_struct_literals(true, |parser| parser.parse_function_args())?
+					} else {
+						Vec::new()
+					};
+
+					Ok(ASTNode::new(NodeKind::VariantLiteral { enum_name: c, variant, values }, self.span_from(&start)))
 				} else {
-					Ok(ASTNode::Literal(Literal::Identifier(Identifier::Symbol(c))))
+					Ok(ASTNode::new(NodeKind::Literal(Literal::Identifier(Identifier::Symbol(c))), start.clone()))
 				}
 			}
-			_ => { Err(Error::LiteralExpected { received: token })}
+			_ => Err(Error::LiteralExpected { received: token }.with_span(&start)),
+		}?;
+
+		self.parse_postfix_operation(expr, &start)
+	}
+
+	// Parse the '::new(' following the name of a built-in type, up to its arguments
+	pub fn parse_constructor(&mut self) -> Result<()> {
+		self.scan_next()?;
+		self.match_token(&[Token::Colon2])?;
+		self.scan_next()?;
+		match self.match_identifier()? {
+			Identifier::Symbol(symbol) if symbol == "new" => {},
+			id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("new".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+		}
+		self.scan_next()?;
+		self.match_token(&[Token::LeftParen])?;
+		self.scan_next()
+	}
+
+	// Parse any number of '[<expr>]' indexes, '.<field>' field accesses and '.<method>(<args>)' method calls following expr
+	pub fn parse_postfix_operation(&mut self, mut expr: ASTNode, start: &Span) -> Result<ASTNode> {
+		while let Ok(token) = self.match_token(&[Token::LeftBracket, Token::Dot]) {
+			self.scan_next()?;
+
+			let kind = if let Token::LeftBracket = token {
+				let index = Box::new(self.parse_with_struct_literals(true, |parser| parser.parse_binary_operation(0))?);
+				self.match_token(&[Token::RightBracket])?;
+				self.scan_next()?;
+
+				NodeKind::Index { array: Box::new(expr), index }
+			} else {
+				let field = match self.match_identifier()? {
+					Identifier::Symbol(symbol) => symbol,
+					id => return Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id }.with_span(&self.current_span)),
+				};
+				self.scan_next()?;
+
+				if self.match_token(&[Token::LeftParen]).is_ok() {
+					self.scan_next()?;
+					let args = self.parse_with_struct_literals(true, |parser| parser.parse_function_args())?;
+					NodeKind::MethodCall { expr: Box::new(expr), method: field, args }
+				} else {
+					NodeKind::FieldAccess { expr: Box::new(expr), field }
+				}
+			};
+
+			expr = ASTNode::new(kind, self.span_from(start));
 		}
+
+		Ok(expr)
 	}
 
 	// Get precedence of token or error if not a valid operator
 	pub fn get_precedence(&self, token: &Token) -> Result<u8> {
 		// Search precedence array for token, else invalid token
-		for (_, prec) in OPERATOR_PRECEDENCE.iter().enumerate() {
-			if prec.0 == *token {
-				return Ok(prec.1);
-			}
+		OPERATOR_PRECEDENCE.iter()
+			.find_map(|prec| if prec.0 == *token { Some(prec.1) } else { None })
+			.ok_or_else(|| Error::BinaryOperatorExpected { received: token.clone() }.with_span(&self.current_span))
+	}
+
+	// Parse a terminal node followed by any number of 'as <type>' casts
+	// Casts bind tighter than binary operators, but looser than unary operators
+	pub fn parse_cast_operation(&mut self) -> Result<ASTNode> {
+		let start = self.current_span.clone();
+		let mut expr = self.parse_terminal_node()?;
+
+		while let Some(Token::Literal(Literal::Identifier(Identifier::As))) = self.current_token {
+			self.scan_next()?;
+			let cast_type = self.parse_type()?;
+			expr = ASTNode::new(NodeKind::Cast { expr: Box::new(expr), cast_type }, self.span_from(&start));
 		}
-		Err(Error::BinaryOperatorExpected { received: token.clone() })
+
+		Ok(expr)
 	}
 
 	pub fn parse_binary_operation(&mut self, prev: u8) -> Result<ASTNode> {
-		let mut left = self.parse_terminal_node()?;
+		let mut left = self.parse_cast_operation()?;
 		let mut right: ASTNode;
 
 		let mut token: Token;
 
 		match &self.current_token {
 			Some(t) => { token = t.clone(); },
-			None => { return Err(Error::BinaryOperatorExpected { received: Token::None }); }
+			None => { return Err(Error::BinaryOperatorExpected { received: Token::None }.with_span(&self.current_span)); }
 		}
 
-		let expr_finishers = [Token::Semicolon, Token::LeftCurly, Token::RightCurly, Token::RightParen, Token::Comma];
+		// No operand can be followed by a name, so 'step' only ends one where it follows a for loop's range
+		let expr_finishers = [Token::Semicolon, Token::LeftCurly, Token::RightCurly, Token::RightParen, Token::RightBracket, Token::Comma, Token::DotDot, Token::DotDotEqual, Token::Literal(Literal::Identifier(Identifier::Symbol(String::from("step"))))];
 
 		if let Token::EndOfFile = token {
-			return Err(Error::InvalidToken { expected: expr_finishers.to_vec(), received: Token::EndOfFile });
-		} 
-		
+			return Err(Error::InvalidToken { expected: expr_finishers.to_vec(), received: Token::EndOfFile }.with_span(&self.current_span));
+		}
+
 		if self.match_token(&expr_finishers).is_ok() {
 			return Ok(left);
 		}
@@ -370,20 +1071,21 @@ impl Parser {
 			right = self.parse_binary_operation(self.get_precedence(&token)?)?;
 
 			// Join left and right into parent node connected by operator token
-			left = ASTNode::Binary { token: token.clone(), left: Box::new(left), right: Box::new(right) };
+			let span = left.span.to(&right.span);
+			left = ASTNode::new(NodeKind::Binary { token: token.clone(), left: Box::new(left), right: Box::new(right) }, span);
 
 			// If EOF reached, return the new left
 			if let Some(Token::EndOfFile) = self.current_token {
 				return Ok(left);
 			}
-			
+
 			if self.match_token(&expr_finishers).is_ok() {
 				return Ok(left);
 			}
 
 			match &self.current_token {
 				Some(t) => { token = t.clone(); },
-				None => { return Err(Error::BinaryOperatorExpected { received: Token::None }); }
+				None => { return Err(Error::BinaryOperatorExpected { received: Token::None }.with_span(&self.current_span)); }
 			}
 		}
 
@@ -397,11 +1099,19 @@ impl Parser {
 		let mut statements = Vec::new();
 
 		while self.current_token != Some(Token::RightCurly) {
-			let statement = self.parse_statement()?;
-			if let Some(node) = statement {
-				statements.push(node);
-			} else {
-				return Err(Error::UnexpectedEOF { expected: Token::RightCurly });
+			// A function definition can't start inside a block, so the block must be missing its '}'
+			if let Some(Token::Literal(Literal::Identifier(Identifier::Function))) = self.current_token {
+				return Err(Error::InvalidToken { expected: [Token::RightCurly].to_vec(), received: self.current_token.clone().unwrap() }.with_span(&self.current_span));
+			}
+
+			// Record invalid statements and continue with the next one
+			match self.parse_statement() {
+				Ok(Some(node)) => statements.push(node),
+				Ok(None) => return Err(Error::UnexpectedEOF { expected: Token::RightCurly }.with_span(&self.current_span)),
+				Err(error) => {
+					self.diagnostics.push(error);
+					self.synchronize_statement();
+				},
 			}
 		}
 
@@ -1,29 +1,36 @@
 pub mod token;
+pub mod span;
 
 use token::*;
+use span::*;
 use utf8_chars::BufReadCharsExt;
 
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::BufReader;
+use std::rc::Rc;
 
 use crate::error::*;
 
 #[derive(Debug)]
 pub struct Scanner {
 	file: BufReader<File>,
-	put_backs: Vec<char>,
-	filename: String,
-	line: usize,
+	put_backs: Vec<(char, Position)>,
+	filename: Rc<str>,
+	position: Position,
+	last_position: Position,
+	token_start: Position,
 }
 
 impl Scanner {
 	pub fn new(filename: String, file: BufReader<File>) -> Self {
 		Self {
 			file,
-			filename,
-			put_backs: [].to_vec(),
-			line: 1,
+			filename: filename.into(),
+			put_backs: Vec::new(),
+			position: Position::default(),
+			last_position: Position::default(),
+			token_start: Position::default(),
 		}
 	}
 
@@ -34,31 +41,35 @@ impl Scanner {
 			.map_err(|cause| Error::FileOpenError { cause })
 	}
 
-	// Put character in put backs
-	pub fn put_back(&mut self, c: char) -> () {
-		self.put_backs.push(c);
+	// Put last read character in put backs and rewind position to it
+	pub fn put_back(&mut self, c: char) {
+		self.put_backs.push((c, self.last_position));
+		self.position = self.last_position;
 	}
 
 	// Get next character in reader
 	pub fn next_char(&mut self) -> Result<Option<char>> {
 		// If there are any characters on put back, return the top
-		if let Some(c) = self.put_backs.pop() {
-			return Ok(Some(c));
+		let next = match self.put_backs.pop() {
+			Some((c, position)) => {
+				self.position = position;
+				Some(c)
+			},
+			None => self.file.read_char().map_err(|cause| Error::FileReadError { cause }.with_span(&self.span_from(self.position)))?,
+		};
+
+		// Advance position past the character so tokens know where they end
+		if let Some(c) = next {
+			self.last_position = self.position;
+			self.position = self.position.advance(c);
 		}
 
-		let next = self.file.read_char()
-			.map_err(|cause| Error::FileReadError { cause });
-
-		if let Ok(Some(c)) = next {
-			// If next is line break, add one to line counter
-			if c == '\n' {
-				self.line += 1;
-			}
+		Ok(next)
+	}
 
-			return Ok(Some(c));
-		}
-		
-		Ok(None)
+	// Create a span from start to the current position
+	pub fn span_from(&self, start: Position) -> Span {
+		Span::new(self.filename.clone(), start, self.position)
 	}
 
 	// Skip over all whitespace and return next char
@@ -96,11 +107,19 @@ impl Scanner {
 		}
 	}
 
+	// Scan in next token along with the span it covers
+	pub fn scan(&mut self) -> Result<Option<SpannedToken>> {
+		let token = self.scan_token().map_err(|error| error.with_span(&self.span_from(self.token_start)))?;
+
+		Ok(token.map(|token| SpannedToken { token, span: self.span_from(self.token_start) }))
+	}
+
 	// Scan in next token and return result
-	pub fn scan(&mut self) -> Result<Option<Token>> {
+	pub fn scan_token(&mut self) -> Result<Option<Token>> {
 		let next = self.skip_whitespace()?;
+		self.token_start = if next.is_some() { self.last_position } else { self.position };
 
-		if let Some(mut c) = next {
+		if let Some(c) = next {
 			// Check if c is a /, if it is, check if next character is a slash; if it is, scan next character until new line is reached
 			if c == '/' {
 				let next = self.next_char()?;
@@ -109,7 +128,7 @@ impl Scanner {
 						// Line comment
 						self.scan_line_comment()?;
 
-						return Ok(self.scan()?);
+						return self.scan_token();
 					} else {
 						self.put_back(next_char);
 					}
@@ -117,10 +136,16 @@ impl Scanner {
 			}
 
 			// Check if c is the start of a literal
-			if c.is_numeric() {
-				let num = self.scan_integer_literal(c)?;
+			if c.is_ascii_digit() {
+				let num = self.scan_number_literal(c)?;
 
-				return Ok(Some(Token::Literal(Literal::Integer(num))));
+				return Ok(Some(Token::Literal(num)));
+			}
+
+			if c == '"' {
+				let string = self.scan_string_literal()?;
+
+				return Ok(Some(Token::Literal(Literal::String(string))));
 			}
 
 			// Check if c is start of an identifier
@@ -128,7 +153,7 @@ impl Scanner {
 				let identifier = self.scan_identifier(c)?;
 
 				// Search for identifier in list of identifiers; if not found, error
-				for (_, id) in IDENTIFIER_SYMBOLS.iter().enumerate() {
+				for id in IDENTIFIER_SYMBOLS.iter() {
 					if identifier.eq(id.0) {
 						return Ok(Some(Token::Literal(Literal::Identifier(id.1.clone()))));
 					}
@@ -138,71 +163,172 @@ impl Scanner {
 			}
 
 			// Generate possible symbols that c represents
-			let mut remaining_symbols: Vec<&(&str, Token)> = Vec::new();
+			let mut remaining_symbols: Vec<&(&str, Token)> = TOKEN_SYMBOLS.iter().filter(|symbol| symbol.0.starts_with(c)).collect();
 			let mut curr: String = String::from(c);
-			
-			for (_, symbol) in TOKEN_SYMBOLS.iter().enumerate() {
-				if symbol.0.chars().nth(0).unwrap() == c {
-					remaining_symbols.push(symbol);
-				}
-			}
 
-			while remaining_symbols.len() >= 1 {
-				// If current symbol matches only remaining, don't let it get removed due to mismatch
-				if remaining_symbols.len() == 1 && remaining_symbols[0].0 == curr {
+			// Keep extending the symbol while some symbol still starts with it, so the longest match wins
+			while remaining_symbols.iter().any(|symbol| symbol.0.len() > curr.len()) {
+				let Some(next) = self.next_char()? else {
 					break;
-				}
+				};
+
+				let extended = format!("{curr}{next}");
+				if remaining_symbols.iter().any(|symbol| symbol.0.starts_with(&extended)) {
+					curr = extended;
 
-				// If character is alphanumeric/whitespace or EOF reached, stop reading symbols
-				if let Some(next) = self.next_char()? {
-					c = next;
+					// Remove symbols that don't match
+					remaining_symbols.retain(|symbol| symbol.0.starts_with(&curr));
 				} else {
+					self.put_back(next);
 					break;
 				}
+			}
 
-				if c.is_alphanumeric() || c.is_whitespace() {
-					self.put_back(c);
-					break;
-				} else {
-					curr.push(c);
+			// Token is invalid unless a symbol matches exactly
+			remaining_symbols.iter()
+				.find(|symbol| symbol.0 == curr)
+				.map(|symbol| Some(symbol.1.clone()))
+				.ok_or(Error::UnknownToken { received: curr })
+		} else {
+			Ok(Some(Token::EndOfFile))
+		}
+	}
 
-					// Remove symbols that don't match
-					remaining_symbols.retain(|symbol| symbol.0.starts_with(&curr));
-				}
+	// Scan in the rest of a string literal after its opening quote, replacing escape sequences
+	pub fn scan_string_literal(&mut self) -> Result<String> {
+		let mut res = String::new();
+		let mut invalid_escape = None;
+
+		loop {
+			match self.next_char()? {
+				// Report a bad escape only once the whole string is consumed, so scanning resumes after it
+				Some('"') => return invalid_escape.map_or(Ok(res), Err),
+				Some('\\') => match self.scan_escape_sequence() {
+					Ok(c) => res.push(c),
+					Err(Error::UnterminatedString) => return Err(Error::UnterminatedString),
+					Err(e) => { invalid_escape.get_or_insert(e); },
+				},
+				Some(c) => res.push(c),
+				None => return Err(Error::UnterminatedString),
 			}
+		}
+	}
 
-			// If possible symbols is empty, token is invalid
-			if remaining_symbols.len() == 0 {
-				Ok(Some(Token::None))
-			} else {
-				for (_, symbol) in remaining_symbols.iter().enumerate() {
-					if symbol.0 == curr {
-						return Ok(Some(symbol.1.clone()));
+	// Scan in the character an escape sequence stands for, after its backslash
+	pub fn scan_escape_sequence(&mut self) -> Result<char> {
+		match self.next_char()? {
+			Some('n') => Ok('\n'),
+			Some('t') => Ok('\t'),
+			Some('"') => Ok('"'),
+			Some('\\') => Ok('\\'),
+			Some('u') => {
+				// Unicode escapes look like \u{1F600}
+				let mut sequence = String::from("\\u");
+				match self.next_char()? {
+					Some('{') => {},
+					Some(c) => {
+						// Leave a closing quote for the string literal to find
+						self.put_back(c);
+						return Err(Error::InvalidEscape { sequence });
+					},
+					None => return Err(Error::UnterminatedString),
+				}
+				sequence.push('{');
+
+				loop {
+					match self.next_char()? {
+						Some('}') => break,
+						Some(c) if c.is_ascii_hexdigit() && sequence.len() < 9 => sequence.push(c),
+						Some('"') => {
+							self.put_back('"');
+							return Err(Error::InvalidEscape { sequence });
+						},
+						Some(c) => {
+							sequence.push(c);
+							return Err(Error::InvalidEscape { sequence });
+						},
+						None => return Err(Error::UnterminatedString),
 					}
 				}
 
-				Err(Error::UnknownToken { received: curr })
+				u32::from_str_radix(&sequence[3..], 16).ok()
+					.and_then(char::from_u32)
+					.ok_or(Error::InvalidEscape { sequence: sequence + "}" })
+			},
+			Some(c) => Err(Error::InvalidEscape { sequence: format!("\\{c}") }),
+			None => Err(Error::UnterminatedString),
+		}
+	}
+
+	// Scan in an integer literal, or a float literal if it has a fraction or exponent, e.g. 1.5 or 2e-3
+	pub fn scan_number_literal(&mut self, c: char) -> Result<Literal> {
+		let mut res = String::from(c);
+		let mut is_float = false;
+		self.scan_digits(&mut res)?;
+
+		// A dot only starts a fraction if a digit follows, so ranges like 0..10 still scan as integers
+		if let Some('.') = self.peek_char()? {
+			let dot_position = self.position;
+			self.next_char()?;
+
+			match self.peek_char()? {
+				Some(d) if d.is_ascii_digit() => {
+					res.push('.');
+					self.scan_digits(&mut res)?;
+					is_float = true;
+				},
+				_ => {
+					self.put_backs.push(('.', dot_position));
+					self.position = dot_position;
+				},
 			}
+		}
+
+		if let Some(e @ ('e' | 'E')) = self.peek_char()? {
+			self.next_char()?;
+			res.push(e);
+
+			if let Some(sign @ ('+' | '-')) = self.peek_char()? {
+				self.next_char()?;
+				res.push(sign);
+			}
+
+			if !matches!(self.peek_char()?, Some(d) if d.is_ascii_digit()) {
+				return Err(Error::InvalidNumber { literal: res });
+			}
+			self.scan_digits(&mut res)?;
+			is_float = true;
+		}
+
+		if is_float {
+			res.parse().map(Literal::Float).map_err(|_| Error::InvalidNumber { literal: res })
 		} else {
-			Ok(Some(Token::EndOfFile))
+			res.parse().map(Literal::Integer).map_err(|cause| Error::StringParseError { cause })
 		}
 	}
 
-	// Scan in integer literal
-	pub fn scan_integer_literal(&mut self, mut c: char) -> Result<i64> {
-		let mut res: i64 = 0;
-		while c.is_numeric() {
-			res = res * 10 + (c as i64 - ('0' as i64));
-			
-			match self.next_char()? {
-				Some(next) => {c = next;},
-				None => return Ok(res)
+	// Append digits to res until a character that isn't a digit is reached
+	fn scan_digits(&mut self, res: &mut String) -> Result<()> {
+		while let Some(d) = self.peek_char()? {
+			if !d.is_ascii_digit() {
+				break;
 			}
+
+			self.next_char()?;
+			res.push(d);
 		}
 
-		self.put_back(c);
+		Ok(())
+	}
 
-		Ok(res)
+	// Get next character without consuming it
+	pub fn peek_char(&mut self) -> Result<Option<char>> {
+		let next = self.next_char()?;
+		if let Some(c) = next {
+			self.put_back(c);
+		}
+
+		Ok(next)
 	}
 
 	// Scan in identifier
@@ -226,11 +352,15 @@ impl Scanner {
 		&self.file
 	}
 
-	pub fn put_backs(&self) -> &[char] {
+	pub fn put_backs(&self) -> &[(char, Position)] {
 		&self.put_backs
 	}
 
-	pub fn filename(&self) -> &String {
+	pub fn filename(&self) -> &str {
 		&self.filename
 	}
+
+	pub fn position(&self) -> Position {
+		self.position
+	}
 }
\ No newline at end of file
@@ -0,0 +1,80 @@
+use std::fmt;
+use std::rc::Rc;
+
+// A line/column location in a source file; both are 1-indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+	pub line: usize,
+	pub column: usize,
+}
+
+impl Position {
+	pub fn new(line: usize, column: usize) -> Self {
+		Self {
+			line,
+			column,
+		}
+	}
+
+	// Position of the character following c
+	pub fn advance(&self, c: char) -> Self {
+		if c == '\n' {
+			Self::new(self.line + 1, 1)
+		} else {
+			Self::new(self.line, self.column + 1)
+		}
+	}
+}
+
+impl Default for Position {
+	fn default() -> Self {
+		Self::new(1, 1)
+	}
+}
+
+impl fmt::Display for Position {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}:{}", self.line, self.column)
+	}
+}
+
+// Region of a source file covered by a token or node; end is exclusive
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Span {
+	filename: Rc<str>,
+	start: Position,
+	end: Position,
+}
+
+impl Span {
+	pub fn new(filename: Rc<str>, start: Position, end: Position) -> Self {
+		Self {
+			filename,
+			start,
+			end,
+		}
+	}
+
+	// Create a span covering self through other
+	pub fn to(&self, other: &Span) -> Span {
+		Span::new(self.filename.clone(), self.start.min(other.start), self.end.max(other.end))
+	}
+
+	pub fn filename(&self) -> &str {
+		&self.filename
+	}
+
+	pub fn start(&self) -> Position {
+		self.start
+	}
+
+	pub fn end(&self) -> Position {
+		self.end
+	}
+}
+
+impl fmt::Display for Span {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}:{}", self.filename, self.start)
+	}
+}
\ No newline at end of file
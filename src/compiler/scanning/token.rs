@@ -1,6 +1,8 @@
 use std::fmt;
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+use super::span::Span;
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Token {
 	EndOfFile,
 	None,
@@ -9,13 +11,20 @@ pub enum Token {
 	RightCurly,
 	LeftParen,
 	RightParen,
+	LeftBracket,
+	RightBracket,
 	Plus,
 	Minus,
 	Asterisk,
 	Slash,
+	Percent,
+	Exclamation,
 	Semicolon,
 	Comma,
 	Colon,
+	Colon2,
+	Dot,
+	Underscore,
 	Equals,
 	Equals2,
 	ExclamationEqual,
@@ -23,53 +32,91 @@ pub enum Token {
 	LessThanEqual,
 	GreaterThan,
 	GreaterThanEqual,
+	Ampersand,
+	Ampersand2,
+	Pipe2,
+	DotDot,
+	DotDotEqual,
 	Arrow,
+	FatArrow,
 }
 
 impl Token {
 	pub fn is_rl_associativity(&self) -> bool {
-		match self {
-			Token::Equals => true,
-			_ => false,
-		}
+		matches!(self, Token::Equals)
+	}
+
+	pub fn is_unary(&self) -> bool {
+		matches!(self, Token::Minus | Token::Exclamation)
 	}
 
 	pub fn is_comparison(&self) -> bool {
-		match self {
-			Token::Equals2 | Token::ExclamationEqual | Token::LessThan | Token::LessThanEqual | Token::GreaterThan | Token::GreaterThanEqual => true,
-			_ => false,
-		}
+		matches!(self, Token::Equals2 | Token::ExclamationEqual | Token::LessThan | Token::LessThanEqual | Token::GreaterThan | Token::GreaterThanEqual)
+	}
+
+	pub fn is_logical(&self) -> bool {
+		matches!(self, Token::Ampersand2 | Token::Pipe2)
 	}
 
-	pub fn get_pnemonic(&self) -> String {
+	// Get the icmp condition for a comparison of signed or unsigned integers
+	pub fn get_pnemonic(&self, signed: bool) -> String {
+		let sign = if signed { "s" } else { "u" };
+
 		match self {
 			Token::Equals2 => String::from("eq"),
 			Token::ExclamationEqual => String::from("ne"),
-			Token::LessThan => String::from("slt"),
-			Token::LessThanEqual => String::from("sle"),
-			Token::GreaterThan => String::from("sgt"),
-			Token::GreaterThanEqual => String::from("sge"),
+			Token::LessThan => format!("{sign}lt"),
+			Token::LessThanEqual => format!("{sign}le"),
+			Token::GreaterThan => format!("{sign}gt"),
+			Token::GreaterThanEqual => format!("{sign}ge"),
+			_ => String::from(""),
+		}
+	}
+
+	// Get the fcmp condition for a comparison of floats; comparisons with NaN are false, except !=
+	pub fn get_float_pnemonic(&self) -> String {
+		match self {
+			Token::Equals2 => String::from("oeq"),
+			Token::ExclamationEqual => String::from("une"),
+			Token::LessThan => String::from("olt"),
+			Token::LessThanEqual => String::from("ole"),
+			Token::GreaterThan => String::from("ogt"),
+			Token::GreaterThanEqual => String::from("oge"),
 			_ => String::from(""),
 		}
 	}
 }
 
+// Token along with the region of source it was scanned from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+	pub token: Token,
+	pub span: Span,
+}
+
 impl fmt::Display for Token {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
 		match self {
 			Token::EndOfFile => write!(f, "EOF"),
 			Token::None => write!(f, "None"),
-			Token::Literal(_) => write!(f, "Literal"),
+			Token::Literal(l) => write!(f, "{l}"),
 			Token::LeftCurly => write!(f, "{{"),
 			Token::RightCurly => write!(f, "}}"),
 			Token::LeftParen => write!(f, "("),
 			Token::RightParen => write!(f, ")"),
+			Token::LeftBracket => write!(f, "["),
+			Token::RightBracket => write!(f, "]"),
 			Token::Plus => write!(f, "+"),
 			Token::Minus => write!(f, "-"),
 			Token::Asterisk => write!(f, "*"),
 			Token::Slash => write!(f, "/"),
+			Token::Percent => write!(f, "%"),
+			Token::Exclamation => write!(f, "!"),
 			Token::Semicolon => write!(f, ";"),
 			Token::Colon => write!(f, ":"),
+			Token::Colon2 => write!(f, "::"),
+			Token::Dot => write!(f, "."),
+			Token::Underscore => write!(f, "_"),
 			Token::Comma => write!(f, ","),
 			Token::Equals => write!(f, "="),
 			Token::Equals2 => write!(f, "=="),
@@ -78,7 +125,13 @@ impl fmt::Display for Token {
 			Token::LessThanEqual => write!(f, "<="),
 			Token::GreaterThan => write!(f, ">"),
 			Token::GreaterThanEqual => write!(f, ">="),
+			Token::Ampersand => write!(f, "&"),
+			Token::Ampersand2 => write!(f, "&&"),
+			Token::Pipe2 => write!(f, "||"),
+			Token::DotDot => write!(f, ".."),
+			Token::DotDotEqual => write!(f, "..="),
 			Token::Arrow => write!(f, "->"),
+			Token::FatArrow => write!(f, "=>"),
 		}
 	}
 }
@@ -86,12 +139,24 @@ impl fmt::Display for Token {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Identifier {
 	Let,
+	Const,
 	Print,
 	If,
 	Else,
 	While,
+	For,
+	In,
+	Break,
+	Continue,
+	As,
 	Function,
 	Return,
+	Struct,
+	Enum,
+	Match,
+	Mut,
+	Box,
+	Vec,
 	Symbol(String),
 }
 
@@ -100,11 +165,23 @@ impl fmt::Display for Identifier {
 		match self {
 			Identifier::Print => write!(f, "print"),
 			Identifier::Let => write!(f, "let"),
+			Identifier::Const => write!(f, "const"),
 			Identifier::If => write!(f, "if"),
 			Identifier::Else => write!(f, "else"),
 			Identifier::While => write!(f, "while"),
+			Identifier::For => write!(f, "for"),
+			Identifier::In => write!(f, "in"),
+			Identifier::Break => write!(f, "break"),
+			Identifier::Continue => write!(f, "continue"),
+			Identifier::As => write!(f, "as"),
 			Identifier::Function => write!(f, "fn"),
 			Identifier::Return => write!(f, "return"),
+			Identifier::Struct => write!(f, "struct"),
+			Identifier::Enum => write!(f, "enum"),
+			Identifier::Match => write!(f, "match"),
+			Identifier::Mut => write!(f, "mut"),
+			Identifier::Box => write!(f, "Box"),
+			Identifier::Vec => write!(f, "Vec"),
 			Identifier::Symbol(s) => write!(f, "{s}"),
 		}
 	}
@@ -112,32 +189,64 @@ impl fmt::Display for Identifier {
 
 pub const IDENTIFIER_SYMBOLS: &[(&str, Identifier)] = &[
 	("let", Identifier::Let),
+	("const", Identifier::Const),
 	("print", Identifier::Print),
 	("if", Identifier::If),
 	("else", Identifier::Else),
 	("while", Identifier::While),
+	("for", Identifier::For),
+	("in", Identifier::In),
+	("break", Identifier::Break),
+	("continue", Identifier::Continue),
+	("as", Identifier::As),
 	("fn", Identifier::Function),
 	("return", Identifier::Return),
+	("struct", Identifier::Struct),
+	("enum", Identifier::Enum),
+	("match", Identifier::Match),
+	("mut", Identifier::Mut),
+	("Box", Identifier::Box),
+	("Vec", Identifier::Vec),
 ];
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
-	Integer(i64),
+	Integer(u64),
+	Float(f64),
+	String(String),
 	Identifier(Identifier)
 }
 
+impl fmt::Display for Literal {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+		match self {
+			Literal::Integer(x) => write!(f, "{x}"),
+			Literal::Float(x) => write!(f, "{x:?}"),
+			Literal::String(s) => write!(f, "{s:?}"),
+			Literal::Identifier(i) => write!(f, "{i}"),
+		}
+	}
+}
+
 pub const TOKEN_SYMBOLS: &[(&str, Token)] = &[
 	("{", Token::LeftCurly),
 	("}", Token::RightCurly),
 	("(", Token::LeftParen),
 	(")", Token::RightParen),
+	("[", Token::LeftBracket),
+	("]", Token::RightBracket),
 	("+", Token::Plus),
 	("-", Token::Minus),
 	("*", Token::Asterisk),
 	("/", Token::Slash),
+	("%", Token::Percent),
+	("!", Token::Exclamation),
 	(";", Token::Semicolon),
 	(",", Token::Comma),
 	(":", Token::Colon),
+	("::", Token::Colon2),
+	(".", Token::Dot),
+	("_", Token::Underscore),
 	("=", Token::Equals),
 	("==", Token::Equals2),
 	("!=", Token::ExclamationEqual),
@@ -145,5 +254,11 @@ pub const TOKEN_SYMBOLS: &[(&str, Token)] = &[
 	("<=", Token::LessThanEqual),
 	(">", Token::GreaterThan),
 	(">=", Token::GreaterThanEqual),
+	("&", Token::Ampersand),
+	("&&", Token::Ampersand2),
+	("||", Token::Pipe2),
+	("..", Token::DotDot),
+	("..=", Token::DotDotEqual),
 	("->", Token::Arrow),
+	("=>", Token::FatArrow),
 ];
\ No newline at end of file
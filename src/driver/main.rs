@@ -7,7 +7,11 @@ fn main() {
 	}
 
 	match cli::compile(&args) {
-		Err(error) => println!("Error: {}", error),
+		Err(diagnostics) => {
+			eprint!("{diagnostics}");
+			eprintln!("error: could not compile due to {} previous error(s)", diagnostics.len());
+			std::process::exit(1);
+		},
 		Ok(_) => println!("{}\nSuccessfully compiled files!", "=".repeat(28)),
 	}
 }